@@ -19,6 +19,7 @@ import {
   useUpdatePreset,
 } from "@/features/presets/hooks";
 import { addonsKeys, getAddonsOptions } from "@/features/addons/hooks";
+import { getPresetDependencies } from "@/features/presets/api";
 import type { Preset } from "@/features/presets/types";
 import { CreatePresetModal } from "@/features/presets/components/create-preset-modal";
 
@@ -38,8 +39,12 @@ export function PresetMenu() {
     },
   });
   const applyPreset = useApplyPreset({
-    onSuccess: () => {
-      toast.success("Preset applied successfully.");
+    onSuccess: (enabledDependencies) => {
+      toast.success(
+        enabledDependencies.length > 0
+          ? `Preset applied successfully, also enabled ${enabledDependencies.join(", ")}.`
+          : "Preset applied successfully."
+      );
       queryClient.refetchQueries({ queryKey: addonsKeys.all });
     },
   });
@@ -70,8 +75,23 @@ export function PresetMenu() {
     createPreset.mutate(preset);
   };
 
-  const handleApplyPreset = (id: Preset["id"]) => {
-    applyPreset.mutate(id);
+  const handleApplyPreset = async (id: Preset["id"]) => {
+    let withDependencies = false;
+    try {
+      const dependencies = await getPresetDependencies(id);
+      if (dependencies.length > 0) {
+        const ok = await confirm({
+          title: "Enable dependencies?",
+          description: `Addons in this preset depend on ${dependencies.join(", ")}, which the preset does not include. Enable them as well?`,
+        });
+        withDependencies = ok === true;
+      }
+    } catch (e) {
+      toast.error((e as Error).message);
+      return;
+    }
+
+    applyPreset.mutate({ id, withDependencies });
   };
 
   const handleSaveToPreset = async (preset: Preset) => {
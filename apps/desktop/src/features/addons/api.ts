@@ -1,20 +1,58 @@
 import { ipc } from "@/lib/ipc";
-import type { Addon, InstallResult, VerificationResult } from "./types";
+import type {
+  Addon,
+  AddonLibrary,
+  AddonMetadata,
+  AddonPage,
+  AddonQuery,
+  DependencyIssue,
+  GameVersionIssue,
+  InstallPreview,
+  InstallResult,
+  LinkRepair,
+  OfficialPackage,
+  UnmanagedPackage,
+  VerificationResult,
+} from "./types";
 
-export const getAddon = (id: string): Promise<Addon> => {
-  return ipc("get_addon", { id });
+export const getAddon = (
+  id: string,
+  withScenery?: boolean
+): Promise<Addon> => {
+  return ipc("get_addon", { id, withScenery });
 };
 
 export const getAddons = (): Promise<Addon[]> => {
   return ipc("get_addons");
 };
 
+export const scanAddons = (): Promise<AddonLibrary> => {
+  return ipc("scan_addons");
+};
+
 export const installAddon = (): Promise<InstallResult> => {
   return ipc("install_addon");
 };
 
-export const enableAddon = (id: string): Promise<void> => {
-  return ipc("enable_addon", { id });
+export const previewInstall = (path: string): Promise<InstallPreview> => {
+  return ipc("preview_install", { path });
+};
+
+export const enableAddon = (
+  id: string,
+  withDependencies?: boolean
+): Promise<string[]> => {
+  return ipc("enable_addon", { id, withDependencies });
+};
+
+export const getAddonDependencies = (
+  id: string
+): Promise<DependencyIssue[]> => {
+  return ipc("get_addon_dependencies", { id });
+};
+
+export const checkDependencies = (): Promise<DependencyIssue[]> => {
+  return ipc("check_dependencies");
 };
 
 export const disableAddon = (id: string): Promise<void> => {
@@ -33,10 +71,62 @@ export const revealAddon = (id: string): Promise<void> => {
   return ipc("reveal_addon", { id });
 };
 
-export const verifyAddon = (id: string): Promise<VerificationResult> => {
-  return ipc("verify_addon", { id });
+export const verifyAddon = (
+  id: string,
+  deep = false
+): Promise<VerificationResult> => {
+  return ipc("verify_addon", { id, deep });
+};
+
+export const repairAddon = (id: string): Promise<string[]> => {
+  return ipc("repair_addon", { id });
+};
+
+export const rebuildAddonLayout = (id: string): Promise<void> => {
+  return ipc("rebuild_addon_layout", { id });
+};
+
+export const queryAddons = (query: AddonQuery): Promise<AddonPage> => {
+  return ipc("query_addons", { query });
+};
+
+export const listUnmanagedPackages = (): Promise<UnmanagedPackage[]> => {
+  return ipc("list_unmanaged_packages");
+};
+
+export const importUnmanagedPackage = (id: string): Promise<void> => {
+  return ipc("import_unmanaged_package", { id });
+};
+
+export const listOfficialPackages = (): Promise<OfficialPackage[]> => {
+  return ipc("list_official_packages");
+};
+
+export const setOfficialPackagesActive = (
+  packages: Record<string, boolean>
+): Promise<void> => {
+  return ipc("set_official_packages_active", { packages });
+};
+
+export const reconcileLinks = (dryRun = false): Promise<LinkRepair[]> => {
+  return ipc("reconcile_links", { dryRun });
 };
 
 export const getAddonThumbnail = async (id: string): Promise<string> => {
   return ipc("get_addon_thumbnail", { id });
 };
+
+export const checkGameVersions = (): Promise<GameVersionIssue[]> => {
+  return ipc("check_game_versions");
+};
+
+export const updateAddonMetadata = (
+  id: string,
+  metadata: AddonMetadata
+): Promise<void> => {
+  return ipc("update_addon_metadata", { id, metadata });
+};
+
+export const listAddonTags = (): Promise<string[]> => {
+  return ipc("list_addon_tags");
+};
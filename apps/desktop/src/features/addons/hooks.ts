@@ -12,9 +12,11 @@ import {
   enableAddon,
   getAddon,
   getAddons,
+  scanAddons,
   getAddonThumbnail,
   installAddon,
   renameAddon,
+  repairAddon,
   uninstallAddon,
   verifyAddon,
 } from "./api";
@@ -22,6 +24,7 @@ import type { InstallResult, VerificationResult } from "./types";
 
 export const addonsKeys = {
   all: ["addons"],
+  library: () => [...addonsKeys.all, "library"],
   addon: (id: string) => ["addon", id],
   thumbnail: (id: string) => [...addonsKeys.addon(id), "thumbnail"],
 };
@@ -57,6 +60,13 @@ export const useGetAddons = (
   return useQuery({ ...getAddonsOptions(), ...options });
 };
 
+export const useScanAddons = () => {
+  return useQuery({
+    queryKey: addonsKeys.library(),
+    queryFn: scanAddons,
+  });
+};
+
 export const useInstallAddon = (
   options?: MutateOptions<InstallResult, Error, void, unknown>
 ) => {
@@ -68,10 +78,10 @@ export const useInstallAddon = (
 };
 
 export const useEnableAddon = (
-  options?: MutateOptions<void, Error, string, unknown>
+  options?: MutateOptions<string[], Error, string, unknown>
 ) => {
   return useMutation({
-    mutationFn: enableAddon,
+    mutationFn: (id: string) => enableAddon(id),
     onError: (e) => toast.error(e.message),
     ...options,
   });
@@ -108,10 +118,25 @@ export const useRenameAddon = (
 };
 
 export const useVerifyAddon = (
-  options?: MutateOptions<VerificationResult, Error, string, unknown>
+  options?: MutateOptions<
+    VerificationResult,
+    Error,
+    { id: string; deep?: boolean },
+    unknown
+  >
+) => {
+  return useMutation({
+    mutationFn: ({ id, deep }) => verifyAddon(id, deep),
+    onError: (e) => toast.error(e.message),
+    ...options,
+  });
+};
+
+export const useRepairAddon = (
+  options?: MutateOptions<string[], Error, string, unknown>
 ) => {
   return useMutation({
-    mutationFn: verifyAddon,
+    mutationFn: (id) => repairAddon(id),
     onError: (e) => toast.error(e.message),
     ...options,
   });
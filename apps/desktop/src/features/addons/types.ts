@@ -1,3 +1,5 @@
+import type { AddonScenerySummary } from "@/features/scenery/types";
+
 export interface Addon {
   id: string;
   name: string;
@@ -6,6 +8,91 @@ export interface Addon {
   content_type: string;
   enabled: boolean;
   size: number;
+  scenery?: AddonScenerySummary;
+  provenance?: Provenance;
+  tags: string[];
+  notes: string;
+  favorite: boolean;
+}
+
+export type AddonSortField =
+  | "relevance"
+  | "id"
+  | "name"
+  | "creator"
+  | "version"
+  | "content_type"
+  | "size"
+  | "enabled"
+  | "favorite";
+
+export interface AddonQuery {
+  text?: string;
+  content_types?: string[];
+  enabled?: boolean;
+  favorite?: boolean;
+  min_size?: number;
+  max_size?: number;
+  tags?: string[];
+  sort?: AddonSortField;
+  descending?: boolean;
+  offset?: number;
+  limit?: number;
+}
+
+export interface AddonPage {
+  addons: Addon[];
+  total: number;
+}
+
+export interface AddonMetadata {
+  tags: string[];
+  notes: string;
+  favorite: boolean;
+}
+
+export interface Provenance {
+  source_file: string;
+  archive_hash: string | null;
+  installed_at: number;
+  installer_version: string | null;
+  previous_version: string | null;
+}
+
+export interface UnrecognizedEntry {
+  id: string;
+  error: string;
+}
+
+export interface UnmanagedPackage {
+  id: string;
+  name: string;
+  creator: string;
+  version: string;
+  content_type: string;
+  size: number;
+  conflict: boolean;
+}
+
+export interface OfficialPackage {
+  id: string;
+  name: string;
+  creator: string;
+  version: string;
+  content_type: string;
+  active: boolean;
+}
+
+export interface LinkRepair {
+  name: string;
+  target: string;
+  problem: "dangling" | "foreign" | "duplicate" | "misnamed";
+  action: "relinked" | "removed" | "skipped";
+}
+
+export interface AddonLibrary {
+  addons: Addon[];
+  unrecognized: UnrecognizedEntry[];
 }
 
 export interface VerificationResult {
@@ -14,11 +101,34 @@ export interface VerificationResult {
 }
 
 export interface VerificationNode {
-  status: "Ok" | "SizeMismatch" | "NotFound";
+  status:
+    | "Ok"
+    | "SizeMismatch"
+    | "NotFound"
+    | "Modified"
+    | "Extra"
+    | "DateMismatch";
   path: string;
   size: number;
 }
 
+export interface PackagePreview {
+  id: string;
+  name: string;
+  creator: string;
+  version: string;
+  content_type: string;
+  size: number;
+  status: "new" | "upgrade" | "reinstall" | "downgrade" | "conflict";
+  installed_version: string | null;
+  dependency_issues: DependencyIssue[];
+}
+
+export interface InstallPreview {
+  packages: PackagePreview[];
+  total_size: number;
+}
+
 export interface InstallResult {
   results: AddonInstallResult[];
 }
@@ -33,3 +143,19 @@ export type AddonInstallResult =
       file: string;
       error: string;
     };
+
+export type DependencyIssue = {
+  addon_id: string;
+  dependency: string;
+  required_version: string;
+} & (
+  | { status: "missing" }
+  | { status: "disabled" }
+  | { status: "outdated"; installed_version: string }
+);
+
+export interface GameVersionIssue {
+  addon_id: string;
+  minimum_game_version: string;
+  min_sim_version: string;
+}
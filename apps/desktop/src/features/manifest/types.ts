@@ -6,9 +6,20 @@ export interface Manifest {
   package_version: string;
   minimum_game_version: string;
   dependencies: Dependency[];
+  release_notes: ReleaseNotes;
+  total_package_size: string;
+  builder: string;
+  package_order_hint: string;
 }
 
 export interface Dependency {
   name: string;
   package_version: string;
 }
+
+export interface ReleaseNotes {
+  neutral: {
+    LastUpdate: string;
+    OlderHistory: string;
+  };
+}
@@ -1,10 +1,15 @@
 import { ipc } from "@/lib/ipc";
 import type { AppSettings } from "@/features/settings/types";
+import type { SimInstall } from "./types";
 
 export const getOnboardingStatus = (): Promise<boolean> => {
   return ipc("get_onboarding_status");
 };
 
+export const detectSimInstalls = (): Promise<SimInstall[]> => {
+  return ipc("detect_sim_installs");
+};
+
 export const completeOnboarding = (settings: AppSettings): Promise<void> => {
   return ipc("complete_onboarding", { settings });
 };
@@ -1,7 +1,11 @@
-import { type MutateOptions, useMutation } from "@tanstack/react-query";
+import {
+  type MutateOptions,
+  useMutation,
+  useQuery,
+} from "@tanstack/react-query";
 import { toast } from "sonner";
 import type { AppSettings } from "@/features/settings/types";
-import { completeOnboarding } from "./api";
+import { completeOnboarding, detectSimInstalls } from "./api";
 
 export const useCompleteOnboarding = (
   options?: MutateOptions<void, Error, AppSettings, unknown>
@@ -12,3 +16,10 @@ export const useCompleteOnboarding = (
     ...options,
   });
 };
+
+export const useDetectSimInstalls = () => {
+  return useQuery({
+    queryKey: ["sim-installs"],
+    queryFn: detectSimInstalls,
+  });
+};
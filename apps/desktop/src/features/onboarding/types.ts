@@ -0,0 +1,8 @@
+export interface SimInstall {
+  version: "2020" | "2024";
+  distribution: "store" | "steam";
+  config_dir: string;
+  packages_dir: string;
+  community_dir: string;
+  proton: boolean;
+}
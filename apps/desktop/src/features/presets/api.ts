@@ -1,5 +1,5 @@
 import { ipc } from "@/lib/ipc";
-import type { Preset } from "./types";
+import type { FlightPlanInput, Preset } from "./types";
 
 export const createPreset = (preset: Preset): Promise<void> => {
   return ipc("create_preset", { preset });
@@ -13,8 +13,32 @@ export const getPreset = (id: string): Promise<Preset[]> => {
   return ipc("get_preset", { id });
 };
 
-export const applyPreset = (id: string): Promise<void> => {
-  return ipc("apply_preset", { id });
+export const getPresetDependencies = (id: string): Promise<string[]> => {
+  return ipc("get_preset_dependencies", { id });
+};
+
+export const applyPreset = ({
+  id,
+  withDependencies,
+}: {
+  id: string;
+  withDependencies?: boolean;
+}): Promise<string[]> => {
+  return ipc("apply_preset", { id, withDependencies });
+};
+
+export const createFlightPlanPreset = (
+  name: string,
+  flightPlan: FlightPlanInput,
+  corridorNm: number,
+  baseAddons: string[]
+): Promise<Preset> => {
+  return ipc("create_flight_plan_preset", {
+    name,
+    flightPlan,
+    corridorNm,
+    baseAddons,
+  });
 };
 
 export const updatePreset = (preset: Preset): Promise<void> => {
@@ -44,7 +44,12 @@ export const useCreatePreset = (
 };
 
 export const useApplyPreset = (
-  options?: MutateOptions<void, Error, string, unknown>
+  options?: MutateOptions<
+    string[],
+    Error,
+    { id: string; withDependencies?: boolean },
+    unknown
+  >
 ) => {
   return useMutation({
     mutationFn: applyPreset,
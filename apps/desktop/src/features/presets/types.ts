@@ -3,4 +3,18 @@ export interface Preset {
   name: string;
   description: string | null;
   addons: string[];
+  tags?: string[];
+  official_packages?: Record<string, boolean>;
 }
+
+export type FlightPlanInput =
+  | {
+      source: "pln";
+      path: string;
+    }
+  | {
+      source: "airports";
+      departure: string | null;
+      destination: string | null;
+      alternates: string[];
+    };
@@ -1,5 +1,9 @@
 import { ipc } from "@/lib/ipc";
-import type { SceneryCache } from "./types";
+import type {
+  AddonScenerySummary,
+  AirportOverride,
+  SceneryCache,
+} from "./types";
 
 export const getSceneryCache = (): Promise<SceneryCache> => {
   return ipc("get_scenery_cache");
@@ -8,3 +12,17 @@ export const getSceneryCache = (): Promise<SceneryCache> => {
 export const rebuildSceneryCache = (): Promise<SceneryCache> => {
   return ipc("rebuild_scenery_cache");
 };
+
+export const getScenerySummaries = (): Promise<AddonScenerySummary[]> => {
+  return ipc("get_scenery_summaries");
+};
+
+export const getAirportOverrides = (
+  addonId?: string
+): Promise<AirportOverride[]> => {
+  return ipc("get_airport_overrides", { addonId });
+};
+
+export const exportSceneryCache = (): Promise<string | null> => {
+  return ipc("export_scenery_cache");
+};
@@ -1,5 +1,13 @@
 export interface SceneryCache {
+  addons: SceneryAddonStats[];
   airports: SceneryAirport[];
+  official_airports: SceneryAirport[];
+}
+
+export interface SceneryAddonStats {
+  addon_id: string;
+  bgl_count: number;
+  bgl_failures: number;
 }
 
 export interface SceneryAirport {
@@ -11,3 +19,26 @@ export interface SceneryAirport {
   altitude: number;
   runway_count: number;
 }
+
+export interface BoundingBox {
+  min_latitude: number;
+  min_longitude: number;
+  max_latitude: number;
+  max_longitude: number;
+}
+
+export interface AddonScenerySummary {
+  addon_id: string;
+  airport_count: number;
+  icaos: string[];
+  bounds: BoundingBox | null;
+  bgl_count: number;
+  bgl_failures: number;
+}
+
+export interface AirportOverride {
+  addon_id: string;
+  icao: string;
+  package_id: string;
+  source: "stock" | "marketplace";
+}
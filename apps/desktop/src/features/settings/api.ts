@@ -1,9 +1,12 @@
 import { ipc } from "@/lib/ipc";
-import type { AppSettings } from "./types";
+import type { ActivationMode, AppSettings } from "./types";
 
 export const DEFAULT_SETTINGS = {
   auto_enable: true,
   auto_clear_scenery_indexes: false,
+  index_official_packages: false,
+  retain_archives: false,
+  activation_mode: "symlink" as ActivationMode,
 };
 
 export const getSettings = (): Promise<AppSettings> => {
@@ -21,3 +24,11 @@ export const updateSetting = <T extends keyof AppSettings>(
 
   return ipc("update_setting", { key, value: data });
 };
+
+export const relocateLibrary = (newDir: string): Promise<void> => {
+  return ipc("relocate_library", { newDir });
+};
+
+export const getPendingRelocation = (): Promise<string | null> => {
+  return ipc("get_pending_relocation");
+};
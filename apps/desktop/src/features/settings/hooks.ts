@@ -4,7 +4,7 @@ import {
   useQuery,
 } from "@tanstack/react-query";
 import { toast } from "sonner";
-import { getSettings, updateSetting } from "./api";
+import { getSettings, relocateLibrary, updateSetting } from "./api";
 import type { AppSettings } from "./types";
 
 export const settingsKeys = {
@@ -29,3 +29,13 @@ export const useUpdateSetting = <T extends keyof AppSettings>(
     ...options,
   });
 };
+
+export const useRelocateLibrary = (
+  options?: MutateOptions<void, Error, string, unknown>
+) => {
+  return useMutation({
+    mutationFn: (newDir) => relocateLibrary(newDir),
+    onError: (e) => toast.error(e.message),
+    ...options,
+  });
+};
@@ -3,4 +3,15 @@ export interface AppSettings {
   community_dir: string;
   auto_enable: boolean;
   auto_clear_scenery_indexes: boolean;
+  index_official_packages: boolean;
+  retain_archives: boolean;
+  activation_mode: ActivationMode;
+}
+
+export type ActivationMode = "symlink" | "hardlink" | "copy";
+
+export interface RelocateProgress {
+  id: string;
+  done: number;
+  total: number;
 }
@@ -5,6 +5,7 @@ import {
   FolderSymlinkIcon,
   Loader2,
   MoreHorizontalIcon,
+  TriangleAlertIcon,
   PencilIcon,
   RefreshCcwIcon,
   Trash2Icon,
@@ -21,14 +22,14 @@ import {
 } from "@aeromod/ui/components/dropdown-menu";
 import { Input } from "@aeromod/ui/components/input";
 import { revealAddon } from "@/features/addons/api";
-import type { Addon } from "@/features/addons/types";
+import type { Addon, UnrecognizedEntry } from "@/features/addons/types";
 import { Hint } from "@/components/hint";
 import {
   useDisableAddon,
   useEnableAddon,
-  useGetAddons,
   useInstallAddon,
   useRenameAddon,
+  useScanAddons,
   useUninstallAddon,
 } from "@/features/addons/hooks";
 import { useConfirm } from "@/hooks/use-confirm";
@@ -40,10 +41,11 @@ export const Route = createFileRoute("/_app/")({
 
 function Index() {
   const {
-    data: addons,
+    data: library,
     isLoading: isLoadingAddons,
     refetch: refetchAddons,
-  } = useGetAddons();
+  } = useScanAddons();
+  const addons = library?.addons;
 
   const install = useInstallAddon({
     onSuccess: ({ results }) => {
@@ -131,6 +133,29 @@ function Index() {
         </div>
       </div>
       <AddonList addons={filteredAddons ?? []} refetchAddons={refetchAddons} />
+      <UnrecognizedList entries={library?.unrecognized ?? []} />
+    </div>
+  );
+}
+
+function UnrecognizedList({ entries }: { entries: UnrecognizedEntry[] }) {
+  if (entries.length === 0) return null;
+
+  return (
+    <div className="flex flex-col gap-y-2">
+      <h2 className="text-lg font-semibold">Unrecognized</h2>
+      {entries.map((entry) => (
+        <div
+          key={entry.id}
+          className="bg-muted flex items-center gap-x-4 rounded-md border px-4 py-2"
+        >
+          <TriangleAlertIcon className="text-destructive size-4 shrink-0" />
+          <div>
+            <p>{entry.id}</p>
+            <p className="text-muted-foreground text-sm">{entry.error}</p>
+          </div>
+        </div>
+      ))}
     </div>
   );
 }
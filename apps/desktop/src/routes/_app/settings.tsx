@@ -16,11 +16,25 @@ import {
   TabsTrigger,
 } from "@aeromod/ui/components/tabs";
 import { Button } from "@aeromod/ui/components/button";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@aeromod/ui/components/select";
 import { Switch } from "@aeromod/ui/components/switch";
 import { Input } from "@aeromod/ui/components/input";
 import { ModeToggle } from "@/components/mode-toggle";
-import { useGetSettings, useUpdateSetting } from "@/features/settings/hooks";
-import type { AppSettings } from "@/features/settings/types";
+import {
+  useGetSettings,
+  useRelocateLibrary,
+  useUpdateSetting,
+} from "@/features/settings/hooks";
+import type {
+  ActivationMode,
+  AppSettings,
+} from "@/features/settings/types";
 
 export const Route = createFileRoute("/_app/settings")({
   component: Settings,
@@ -35,6 +49,8 @@ function Settings() {
   } = useGetSettings();
   const { mutate: updateSetting, isPending: isPendingUpdate } =
     useUpdateSetting({ onSuccess: () => refetchSettings() });
+  const { mutate: relocateLibrary, isPending: isPendingRelocate } =
+    useRelocateLibrary({ onSuccess: () => refetchSettings() });
 
   const [pendingSettings, setPendingSettings] = useState(settings);
   const hasChanges = useMemo(() => {
@@ -52,7 +68,12 @@ function Settings() {
     if (!settings || !pendingSettings || !hasChanges) return;
 
     (Object.keys(pendingSettings) as (keyof AppSettings)[]).forEach((key) => {
-      if (pendingSettings[key] !== settings[key]) {
+      if (pendingSettings[key] === settings[key]) return;
+
+      // Moving the library relinks every addon, not just the setting.
+      if (key === "addons_dir") {
+        relocateLibrary(pendingSettings.addons_dir);
+      } else {
         updateSetting({
           key,
           value: pendingSettings[key],
@@ -139,6 +160,68 @@ function Settings() {
                   }
                 />
               </div>
+              <div className="flex w-full items-center justify-between">
+                <div>
+                  <h3 className="font-medium">Index Official Packages</h3>
+                  <p className="text-muted-foreground text-sm">
+                    Include official and marketplace packages in the scenery
+                    cache to detect overridden airports.
+                  </p>
+                </div>
+                <Switch
+                  checked={pendingSettings.index_official_packages}
+                  onCheckedChange={(checked) =>
+                    setPendingSettings((settings) => ({
+                      ...settings!,
+                      index_official_packages: checked,
+                    }))
+                  }
+                />
+              </div>
+              <div className="flex w-full items-center justify-between">
+                <div>
+                  <h3 className="font-medium">Retain Archives</h3>
+                  <p className="text-muted-foreground text-sm">
+                    Keep a copy of installed archives to repair damaged addons.
+                  </p>
+                </div>
+                <Switch
+                  checked={pendingSettings.retain_archives}
+                  onCheckedChange={(checked) =>
+                    setPendingSettings((settings) => ({
+                      ...settings!,
+                      retain_archives: checked,
+                    }))
+                  }
+                />
+              </div>
+              <div className="flex w-full items-center justify-between">
+                <div>
+                  <h3 className="font-medium">Activation Mode</h3>
+                  <p className="text-muted-foreground text-sm">
+                    How enabled addons are placed in the community folder.
+                    Applies to addons enabled after the change.
+                  </p>
+                </div>
+                <Select
+                  value={pendingSettings.activation_mode}
+                  onValueChange={(value) =>
+                    setPendingSettings((settings) => ({
+                      ...settings!,
+                      activation_mode: value as ActivationMode,
+                    }))
+                  }
+                >
+                  <SelectTrigger className="w-40">
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="symlink">Symlink</SelectItem>
+                    <SelectItem value="hardlink">Hard Links</SelectItem>
+                    <SelectItem value="copy">Copy</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
               <SettingsItemPath
                 name="Addons Directory"
                 description="The directory where downloaded addons are stored."
@@ -180,14 +263,14 @@ function Settings() {
         <div className="mt-auto flex justify-end gap-x-2 border-t pt-4">
           <Button
             variant="outline"
-            disabled={!hasChanges || isPendingUpdate}
+            disabled={!hasChanges || isPendingUpdate || isPendingRelocate}
             onClick={handleDiscardChanges}
           >
             <UndoIcon />
             Discard
           </Button>
           <Button
-            disabled={!hasChanges || isPendingUpdate}
+            disabled={!hasChanges || isPendingUpdate || isPendingRelocate}
             onClick={handleSaveChanges}
           >
             <SaveIcon />
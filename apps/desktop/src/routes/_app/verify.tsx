@@ -1,6 +1,7 @@
 import { useMemo, useState } from "react";
 import { createFileRoute } from "@tanstack/react-router";
 import { AlertCircleIcon, Loader2Icon } from "lucide-react";
+import { toast } from "sonner";
 
 import { Button } from "@aeromod/ui/components/button";
 import {
@@ -11,7 +12,11 @@ import {
   SelectValue,
 } from "@aeromod/ui/components/select";
 import type { VerificationNode } from "@/features/addons/types";
-import { useGetAddons, useVerifyAddon } from "@/features/addons/hooks";
+import {
+  useGetAddons,
+  useRepairAddon,
+  useVerifyAddon,
+} from "@/features/addons/hooks";
 import { humanFileSize } from "@/lib/utils";
 
 export const Route = createFileRoute("/_app/verify")({
@@ -25,6 +30,12 @@ function Verify() {
     data: verification,
     status: verificationStatus,
   } = useVerifyAddon();
+  const { mutate: repair, isPending: isPendingRepair } = useRepairAddon({
+    onSuccess: (repaired, id) => {
+      toast.success(`Restored ${repaired.length} file(s).`);
+      mutate({ id });
+    },
+  });
 
   const sortedFiles = useMemo(
     () =>
@@ -32,7 +43,10 @@ function Verify() {
         const order: Record<VerificationNode["status"], number> = {
           NotFound: 0,
           SizeMismatch: 1,
-          Ok: 2,
+          Modified: 2,
+          Extra: 3,
+          DateMismatch: 4,
+          Ok: 5,
         };
         return order[a.status] - order[b.status];
       }),
@@ -75,10 +89,26 @@ function Verify() {
         </Select>
         <Button
           disabled={!selected}
-          onClick={() => selected && mutate(selected)}
+          onClick={() => selected && mutate({ id: selected })}
         >
           Verify
         </Button>
+        <Button
+          variant="outline"
+          disabled={!selected}
+          onClick={() => selected && mutate({ id: selected, deep: true })}
+        >
+          Deep Verify
+        </Button>
+        {verification && !verification.verified && (
+          <Button
+            variant="outline"
+            disabled={!selected || isPendingRepair}
+            onClick={() => selected && repair(selected)}
+          >
+            Repair
+          </Button>
+        )}
       </div>
       {verificationStatus === "pending" && (
         <div className="flex size-full items-center justify-center">
@@ -108,6 +138,15 @@ function Verify() {
                 {f.status === "NotFound" && (
                   <div className="size-4 rounded-full bg-red-500"></div>
                 )}
+                {f.status === "Modified" && (
+                  <div className="size-4 rounded-full bg-red-500"></div>
+                )}
+                {f.status === "Extra" && (
+                  <div className="size-4 rounded-full bg-blue-500"></div>
+                )}
+                {f.status === "DateMismatch" && (
+                  <div className="size-4 rounded-full bg-yellow-500"></div>
+                )}
                 <p className="text-sm">{f.path}</p>
               </div>
               <div className="flex items-center gap-x-3">
@@ -126,6 +165,24 @@ function Verify() {
                     Not Found
                   </div>
                 )}
+                {f.status === "Modified" && (
+                  <div className="flex items-center gap-x-2 text-sm text-red-500">
+                    <AlertCircleIcon className="size-4" />
+                    Modified
+                  </div>
+                )}
+                {f.status === "Extra" && (
+                  <div className="flex items-center gap-x-2 text-sm text-blue-500">
+                    <AlertCircleIcon className="size-4" />
+                    Extra
+                  </div>
+                )}
+                {f.status === "DateMismatch" && (
+                  <div className="flex items-center gap-x-2 text-sm text-amber-500">
+                    <AlertCircleIcon className="size-4" />
+                    Date Mismatch
+                  </div>
+                )}
               </div>
             </div>
           ))}
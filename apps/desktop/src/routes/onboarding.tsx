@@ -1,4 +1,4 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
 import { createFileRoute, useNavigate } from "@tanstack/react-router";
 import {
@@ -12,7 +12,10 @@ import { Button } from "@aeromod/ui/components/button";
 import { Input } from "@aeromod/ui/components/input";
 import { DEFAULT_SETTINGS } from "@/features/settings/api";
 import type { AppSettings } from "@/features/settings/types";
-import { useCompleteOnboarding } from "@/features/onboarding/hooks";
+import {
+  useCompleteOnboarding,
+  useDetectSimInstalls,
+} from "@/features/onboarding/hooks";
 
 export const Route = createFileRoute("/onboarding")({
   component: Onboarding,
@@ -28,6 +31,17 @@ function Onboarding() {
     ...DEFAULT_SETTINGS,
   });
 
+  const { data: installs } = useDetectSimInstalls();
+  useEffect(() => {
+    if (installs && installs.length > 0) {
+      setSettings((s) =>
+        s.community_dir === ""
+          ? { ...s, community_dir: installs[0].community_dir }
+          : s
+      );
+    }
+  }, [installs]);
+
   const { mutate, isPending } = useCompleteOnboarding({
     onSuccess: () => navigate({ to: "/" }),
     onError: () => navigate({ reloadDocument: true }),
@@ -67,6 +81,31 @@ function Onboarding() {
                   setSettings((s) => ({ ...s, community_dir: v }))
                 }
               />
+              {installs && installs.length > 1 && (
+                <div className="flex flex-wrap gap-2">
+                  {installs.map((install) => (
+                    <Button
+                      key={install.community_dir}
+                      variant={
+                        settings.community_dir === install.community_dir
+                          ? "secondary"
+                          : "outline"
+                      }
+                      size="sm"
+                      onClick={() =>
+                        setSettings((s) => ({
+                          ...s,
+                          community_dir: install.community_dir,
+                        }))
+                      }
+                    >
+                      MSFS {install.version} (
+                      {install.distribution === "store" ? "Store" : "Steam"}
+                      {install.proton && ", Proton"})
+                    </Button>
+                  ))}
+                </div>
+              )}
               <div className="flex items-center gap-x-2">
                 <Button
                   className="w-1/2"
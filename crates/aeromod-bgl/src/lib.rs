@@ -200,7 +200,7 @@ pub fn load_bgl_objects<P: AsRef<Path>>(path: P) -> io::Result<Vec<BglObject>> {
       section_type: reader.read_u32::<LittleEndian>()?,
       subsection_size: reader
         .read_u32::<LittleEndian>()
-        .map(|s| calculate_subsection_header_size(s))?,
+        .map(calculate_subsection_header_size)?,
       subsection_count: reader.read_u32::<LittleEndian>()?,
       subsection_offset: reader.read_u32::<LittleEndian>()?,
       _total_subsection_size: reader.read_u32::<LittleEndian>()?,
@@ -222,12 +222,9 @@ pub fn load_bgl_objects<P: AsRef<Path>>(path: P) -> io::Result<Vec<BglObject>> {
 
       // Parse subsection records.
       reader.seek(SeekFrom::Start(data_offset as u64))?;
-      match FSSectType::from(section.section_type) {
-        FSSectType::Airport => {
-          let airport = parse_airport_record(&mut reader)?;
-          section_objects.push(BglObject::Airport(airport));
-        }
-        _ => {}
+      if let FSSectType::Airport = FSSectType::from(section.section_type) {
+        let airport = parse_airport_record(&mut reader)?;
+        section_objects.push(BglObject::Airport(airport));
       }
 
       bgl_objects.append(&mut section_objects);
@@ -0,0 +1,266 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::{ActivationMode, AppSettings};
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::community;
+
+/// File placed in hard-linked and copied addons in the community folder. It
+/// tells them apart from packages the user put there, which are never
+/// removed.
+const MARKER_NAME: &str = ".aeromod-activation.json";
+
+#[derive(Serialize, Deserialize)]
+struct Marker {
+  /// The addon directory the files come from.
+  source: PathBuf,
+  mode: ActivationMode,
+}
+
+fn read_marker(path: &Path) -> Option<Marker> {
+  let file = File::open(path.join(MARKER_NAME)).ok()?;
+  serde_json::from_reader(BufReader::new(file)).ok()
+}
+
+fn write_marker(path: &Path, marker: &Marker) -> Result<()> {
+  aeromod_fs::write_atomic(path.join(MARKER_NAME), |writer| {
+    serde_json::to_writer(writer, marker).map_err(Into::into)
+  })?;
+  Ok(())
+}
+
+/// Whether `path` in the community folder is a hard-linked or copied addon
+/// created by AeroMod.
+pub(crate) fn is_managed_copy(path: &Path) -> bool {
+  !path.is_symlink() && read_marker(path).is_some()
+}
+
+/// How the addon `id` is enabled in the community folder, whatever the
+/// current activation mode of the library is. `None` if it is not enabled.
+pub fn active_mode(settings: &AppSettings, id: &str) -> Option<ActivationMode> {
+  if community::is_linked(settings, id) {
+    return Some(ActivationMode::Symlink);
+  }
+
+  let path = Path::new(&settings.community_dir).join(id);
+  if path.is_symlink() {
+    return None;
+  }
+
+  let marker = read_marker(&path)?;
+  community::same_path(&marker.source, &Path::new(&settings.addons_dir).join(id))
+    .then_some(marker.mode)
+}
+
+/// Makes the addon `id` visible in the community folder using the activation
+/// mode of the library. The community folder must not hold an entry named
+/// `id` yet.
+pub(crate) fn activate(settings: &AppSettings, id: &str) -> Result<()> {
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  let target_path = Path::new(&settings.community_dir).join(id);
+
+  match settings.activation_mode {
+    ActivationMode::Symlink => {
+      aeromod_fs::symlink_dir(addon_path, target_path).context("Failed to create symlink")
+    }
+    mode => create_copy(&addon_path, &target_path, mode),
+  }
+}
+
+/// Hard links or copies the addon in `addon_path` to `target_path`.
+fn create_copy(addon_path: &Path, target_path: &Path, mode: ActivationMode) -> Result<()> {
+  // The marker is written first so that an interrupted activation is still
+  // recognized, and removed, as AeroMod's own.
+  fs::create_dir_all(target_path)?;
+  let result = write_marker(
+    target_path,
+    &Marker {
+      source: addon_path.to_path_buf(),
+      mode,
+    },
+  )
+  .and_then(|_| populate(addon_path, target_path, mode));
+
+  if let Err(e) = result {
+    let _ = fs::remove_dir_all(target_path);
+    return Err(e);
+  }
+
+  Ok(())
+}
+
+/// Recreates the hard-linked or copied addon `id` in the community folder
+/// from the addons directory, so that it reflects files that were changed
+/// there since it was enabled. Symlinked and disabled addons are left alone.
+///
+/// The new copy is built next to the old one and swapped in, so a failure
+/// keeps the addon enabled as it was.
+pub(crate) fn refresh(settings: &AppSettings, id: &str) -> Result<()> {
+  let mode = match active_mode(settings, id) {
+    None | Some(ActivationMode::Symlink) => return Ok(()),
+    Some(mode) => mode,
+  };
+
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  let target_path = Path::new(&settings.community_dir).join(id);
+  let fresh_path = target_path.with_file_name(community::temp_name(id, "refresh"));
+  let stale_path = target_path.with_file_name(community::temp_name(id, "stale"));
+  for path in [&fresh_path, &stale_path] {
+    if path.exists() {
+      fs::remove_dir_all(path)?;
+    }
+  }
+
+  create_copy(&addon_path, &fresh_path, mode)?;
+  fs::rename(&target_path, &stale_path).inspect_err(|_| {
+    let _ = fs::remove_dir_all(&fresh_path);
+  })?;
+  if let Err(e) = fs::rename(&fresh_path, &target_path) {
+    let _ = fs::rename(&stale_path, &target_path);
+    let _ = fs::remove_dir_all(&fresh_path);
+    return Err(e).context("Failed to replace addon in community folder");
+  }
+
+  fs::remove_dir_all(stale_path).context("Failed to remove the previous copy of the addon")
+}
+
+fn populate(from: &Path, to: &Path, mode: ActivationMode) -> Result<()> {
+  for entry in WalkDir::new(from).min_depth(1) {
+    let entry = entry?;
+    let relative = entry.path().strip_prefix(from)?;
+    let target = to.join(relative);
+
+    if entry.file_type().is_dir() {
+      fs::create_dir_all(&target)?;
+    } else if mode == ActivationMode::Hardlink {
+      fs::hard_link(entry.path(), &target).with_context(|| {
+        format!(
+          "Failed to hard link '{}', the community folder must be on the same drive as the addons directory",
+          relative.display()
+        )
+      })?;
+    } else {
+      fs::copy(entry.path(), &target)
+        .with_context(|| format!("Failed to copy '{}'", relative.display()))?;
+    }
+  }
+
+  Ok(())
+}
+
+/// Removes the addon `id` from the community folder, however it was enabled.
+pub(crate) fn deactivate(settings: &AppSettings, id: &str) -> Result<()> {
+  let target_path = Path::new(&settings.community_dir).join(id);
+
+  if target_path.is_symlink() {
+    aeromod_fs::remove_symlink_dir(target_path).context("Failed to remove symlink")?;
+  } else if is_managed_copy(&target_path) {
+    fs::remove_dir_all(target_path).context("Failed to remove addon from community folder")?;
+  } else {
+    return Err(anyhow!(
+      "Addon '{}' is not enabled or does not exist in the community folder",
+      id
+    ));
+  }
+
+  Ok(())
+}
+
+/// Points the hard-linked or copied addon at `path` in the community folder
+/// to its new location `source`.
+pub(crate) fn retarget(path: &Path, source: &Path) -> Result<()> {
+  let Some(marker) = read_marker(path) else {
+    return Ok(());
+  };
+
+  write_marker(
+    path,
+    &Marker {
+      source: source.to_path_buf(),
+      mode: marker.mode,
+    },
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::addon;
+  use crate::testing::write_package;
+
+  #[test]
+  fn test_activation_modes() {
+    let (_dir, mut settings) = crate::testing::settings();
+
+    let addon_path = settings.addons_dir.join("airport-ltfm");
+    write_package(&addon_path, "Airport");
+    fs::create_dir_all(addon_path.join("scenery")).unwrap();
+    fs::write(addon_path.join("scenery").join("airport.bgl"), "bgl").unwrap();
+
+    let community_path = settings.community_dir.join("airport-ltfm");
+    for mode in [
+      ActivationMode::Symlink,
+      ActivationMode::Hardlink,
+      ActivationMode::Copy,
+    ] {
+      settings.activation_mode = mode;
+      addon::enable_addon(&settings, "airport-ltfm").unwrap();
+      assert_eq!(active_mode(&settings, "airport-ltfm"), Some(mode));
+      assert!(community_path.join("scenery").join("airport.bgl").exists());
+      assert!(
+        addon::parse_addon(&settings, "airport-ltfm")
+          .unwrap()
+          .enabled
+      );
+      assert!(addon::enable_addon(&settings, "airport-ltfm").is_err());
+
+      // Switching modes does not lose track of enabled addons.
+      settings.activation_mode = ActivationMode::Symlink;
+      addon::disable_addon(&settings, "airport-ltfm").unwrap();
+      assert!(!community_path.exists());
+      assert!(active_mode(&settings, "airport-ltfm").is_none());
+      assert!(addon_path.join("scenery").join("airport.bgl").exists());
+    }
+
+    // Packages placed in the community folder by hand are left alone.
+    fs::create_dir_all(&community_path).unwrap();
+    assert!(active_mode(&settings, "airport-ltfm").is_none());
+    assert!(addon::disable_addon(&settings, "airport-ltfm").is_err());
+    assert!(community_path.exists());
+  }
+
+  #[test]
+  fn test_rebuild_layout_refreshes_copies() {
+    let (_dir, mut settings) = crate::testing::settings();
+
+    let addon_path = settings.addons_dir.join("airport-ltfm");
+    write_package(&addon_path, "Airport");
+    fs::write(addon_path.join("airport.bgl"), "bgl").unwrap();
+
+    let community_path = settings.community_dir.join("airport-ltfm");
+    for mode in [ActivationMode::Hardlink, ActivationMode::Copy] {
+      settings.activation_mode = mode;
+      addon::enable_addon(&settings, "airport-ltfm").unwrap();
+
+      // Replaced rather than modified in place, like an update of the file.
+      fs::remove_file(addon_path.join("airport.bgl")).unwrap();
+      fs::write(addon_path.join("airport.bgl"), format!("{:?}", mode)).unwrap();
+      crate::layout::rebuild_addon_layout(&settings, "airport-ltfm").unwrap();
+
+      assert_eq!(active_mode(&settings, "airport-ltfm"), Some(mode));
+      assert_eq!(
+        fs::read_to_string(community_path.join("airport.bgl")).unwrap(),
+        format!("{:?}", mode)
+      );
+      assert!(community_path.join("layout.json").exists());
+      assert_eq!(fs::read_dir(&settings.community_dir).unwrap().count(), 1);
+
+      addon::disable_addon(&settings, "airport-ltfm").unwrap();
+    }
+  }
+}
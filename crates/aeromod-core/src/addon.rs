@@ -1,6 +1,7 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use aeromod_settings::AppSettings;
 use anyhow::{Context, Result, anyhow};
@@ -9,66 +10,185 @@ use serde::{Deserialize, Serialize};
 use tempfile::tempdir;
 use walkdir::WalkDir;
 
+use crate::activation;
+use crate::archive::{self, RetainedArchive};
+use crate::baseline::{self, Baseline};
+use crate::dependency::{DependencyIssue, PackageIndex};
+use crate::layout::{self, Layout};
+use crate::library::{IndexEntry, LibraryIndex};
 use crate::manifest::Manifest;
+use crate::metadata::{AddonMetadata, MetadataStore};
+use crate::provenance::{self, Provenance};
+use crate::scenery::AddonScenerySummary;
+use crate::version::Version;
+
+/// Hidden directory inside the addons directory that holds AeroMod's own
+/// working data. It lives on the same volume as the addons so that moving
+/// packages in and out of it is a cheap rename.
+pub const INTERNAL_DIR_NAME: &str = ".aeromod";
 
 #[derive(Serialize, Deserialize)]
 pub struct Addon {
   pub id: String,
   pub name: String,
   pub creator: String,
-  pub version: String,
+  pub version: Version,
   pub content_type: String,
   pub enabled: bool,
   pub size: u64,
+  /// Scenery coverage, attached on request from the scenery cache.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub scenery: Option<AddonScenerySummary>,
+  /// Install metadata, if the addon was installed by AeroMod.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub provenance: Option<Provenance>,
+  /// Tags, notes and favorite flag, attached from the [`MetadataStore`].
+  #[serde(flatten)]
+  pub metadata: AddonMetadata,
 }
 
 pub fn parse_addon(settings: &AppSettings, id: &str) -> Result<Addon> {
-  let path = Path::new(&settings.addons_dir).join(id);
-  let size = aeromod_fs::get_directory_size(&path).unwrap_or(0);
-
-  if !path.exists() {
-    return Err(anyhow!("Addon '{}' not found in addons directory", id));
+  let entry = IndexEntry::read(&Path::new(&settings.addons_dir).join(id))?;
+  let mut addon = addon_from_entry(settings, id, entry);
+  if let Ok(store) = MetadataStore::open(settings) {
+    addon.metadata = store.get(id);
   }
 
-  if !path.is_dir() {
-    return Err(anyhow!("Not a directory"));
-  };
+  Ok(addon)
+}
 
-  let manifest = Manifest::parse(path.join("manifest.json"))?;
-  let enabled = Path::new(&settings.community_dir).join(id).exists();
+fn addon_from_entry(settings: &AppSettings, id: &str, entry: IndexEntry) -> Addon {
+  let enabled = activation::active_mode(settings, id).is_some();
 
-  Ok(Addon {
+  Addon {
     id: id.to_string(),
-    name: manifest.title,
-    creator: manifest.creator,
-    version: manifest.package_version,
-    content_type: manifest.content_type,
+    name: entry.name,
+    creator: entry.creator,
+    version: entry.version,
+    content_type: entry.content_type,
     enabled,
-    size,
-  })
+    size: entry.size,
+    scenery: None,
+    provenance: Provenance::load(settings, id).ok().flatten(),
+    metadata: AddonMetadata::default(),
+  }
 }
 
-pub fn get_addons(settings: &AppSettings) -> Result<Vec<Addon>> {
+/// A directory in the addons directory that could not be read as an addon.
+#[derive(Serialize, Deserialize)]
+pub struct UnrecognizedEntry {
+  pub id: String,
+  pub error: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddonLibrary {
+  pub addons: Vec<Addon>,
+  pub unrecognized: Vec<UnrecognizedEntry>,
+}
+
+/// Reads every entry of the addons directory, keeping the ones that fail to
+/// parse together with their error instead of dropping them. Manifest data
+/// and sizes come from the library index where it is still current.
+pub fn scan_addons(settings: &AppSettings) -> Result<AddonLibrary> {
   let mut addons: Vec<Addon> = Vec::new();
+  let mut unrecognized: Vec<UnrecognizedEntry> = Vec::new();
 
+  let mut ids = Vec::new();
   for entry in fs::read_dir(&settings.addons_dir)? {
     let path = entry?.path();
-    let name = match path.file_name() {
-      Some(n) => n.to_string_lossy().into_owned(),
-      None => {
-        continue;
-      }
-    };
+    if !path.is_dir() || path.file_name() == Some(INTERNAL_DIR_NAME.as_ref()) {
+      continue;
+    }
+
+    if let Some(name) = path.file_name() {
+      ids.push(name.to_string_lossy().into_owned());
+    }
+  }
+
+  let mut index = LibraryIndex::load(settings);
+  let results = index.read_all(Path::new(&settings.addons_dir), &ids);
+
+  for (id, result) in ids.into_iter().zip(results) {
+    match result {
+      Ok(entry) => addons.push(addon_from_entry(settings, &id, entry)),
+      Err(e) => unrecognized.push(UnrecognizedEntry {
+        id,
+        error: format!("{:#}", e),
+      }),
+    }
+  }
+
+  if let Ok(store) = MetadataStore::open(settings) {
+    store.apply(&mut addons);
+  }
+
+  Ok(AddonLibrary {
+    addons,
+    unrecognized,
+  })
+}
+
+pub fn get_addons(settings: &AppSettings) -> Result<Vec<Addon>> {
+  Ok(scan_addons(settings)?.addons)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InstallOptions {
+  /// Update addons that are already installed instead of failing.
+  pub update_existing: bool,
+  /// Allow an update to install the same or an older version.
+  pub allow_downgrade: bool,
+  /// Version of the application performing the install, recorded in the
+  /// addon's [`Provenance`].
+  pub installer_version: Option<String>,
+}
+
+/// Outcome of installing a single package from an archive or folder.
+pub struct PackageInstall {
+  pub id: String,
+  pub result: Result<()>,
+}
+
+/// Finds every package directory below `root`. A package is a directory
+/// containing a `manifest.json`; its own subdirectories are not searched.
+pub fn find_packages(root: &Path) -> Result<Vec<PathBuf>> {
+  let mut packages = Vec::new();
+
+  let mut entries = WalkDir::new(root).sort_by_file_name().into_iter();
+  while let Some(entry) = entries.next() {
+    let entry = entry?;
+    if !entry.file_type().is_dir() {
+      continue;
+    }
 
-    if let Ok(addon) = parse_addon(settings, &name) {
-      addons.push(addon);
+    // Resource forks added by the macOS archive utility.
+    if entry.file_name() == "__MACOSX" {
+      entries.skip_current_dir();
+      continue;
+    }
+
+    if entry.path().join("manifest.json").is_file() {
+      packages.push(entry.path().to_path_buf());
+      entries.skip_current_dir();
     }
   }
 
-  Ok(addons)
+  Ok(packages)
 }
 
-pub fn install_addon(settings: &AppSettings, src: &Path) -> Result<String> {
+/// Installs every package found in `src`, which is either an archive or a
+/// folder. Fails only if no package could be found; errors of individual
+/// packages are reported in their [`PackageInstall`].
+pub fn install_addon(settings: &AppSettings, src: &Path) -> Result<Vec<PackageInstall>> {
+  install_addon_with(settings, src, &InstallOptions::default())
+}
+
+pub fn install_addon_with(
+  settings: &AppSettings,
+  src: &Path,
+  options: &InstallOptions,
+) -> Result<Vec<PackageInstall>> {
   if !src.exists() {
     return Err(anyhow!("Directory '{}' does not exist", src.display()));
   }
@@ -82,36 +202,365 @@ pub fn install_addon(settings: &AppSettings, src: &Path) -> Result<String> {
     tmp_guard.path().to_path_buf()
   };
 
-  let mut addon_dir = None;
-  for entry in WalkDir::new(&search_dir) {
-    let entry = entry?;
-    let path = entry.path();
-    let file_name = entry.file_name();
+  let packages = find_packages(&search_dir)?;
+  if packages.is_empty() {
+    return Err(anyhow!("No manifest.json found"));
+  }
+
+  let archive_hash = if src.is_file() {
+    baseline::hash_file(src).ok()
+  } else {
+    None
+  };
+
+  let results: Vec<_> = packages
+    .iter()
+    .map(|package_dir| {
+      let id = package_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+      let previous_version = parse_addon(settings, &id).ok().map(|a| a.version);
+      let result = install_package(settings, package_dir, options);
+
+      if result.is_ok() {
+        let provenance = Provenance {
+          archive_hash: archive_hash.clone(),
+          installer_version: options.installer_version.clone(),
+          previous_version,
+          ..Provenance::new(src)
+        };
+        let _ = provenance.save(settings, &id);
+      }
+
+      PackageInstall { id, result }
+    })
+    .collect();
 
-    if path.is_file() && file_name == "manifest.json" {
-      addon_dir = path.parent().map(|p| p.to_path_buf());
-      break;
+  let retain = settings.retain_archives && results.iter().any(|r| r.result.is_ok());
+  let archive = archive_hash
+    .as_deref()
+    .filter(|_| retain)
+    .and_then(|hash| archive::store_archive(settings, src, hash).ok());
+
+  for (package_dir, install) in packages.iter().zip(&results) {
+    if install.result.is_err() {
+      continue;
+    }
+
+    // The install itself succeeded, so a failure to retain the archive only
+    // means the addon cannot be repaired from it later. Replacing the record
+    // releases the archive of the previous install.
+    let retained = archive.as_ref().and_then(|archive| {
+      Some(RetainedArchive {
+        archive: archive.clone(),
+        package_root: baseline::relative_path(&search_dir, package_dir).ok()?,
+      })
+    });
+    let saved = retained.is_some_and(|r| r.save(settings, &install.id).is_ok());
+    if !saved {
+      let _ = archive::release_archive(settings, &install.id);
     }
   }
 
-  let addon_dir = addon_dir.context("No manifest.json found")?;
+  Ok(results)
+}
 
-  let id = addon_dir
+fn install_package(
+  settings: &AppSettings,
+  package_dir: &Path,
+  options: &InstallOptions,
+) -> Result<()> {
+  let id = package_dir
     .file_name()
     .context("Failed to get addon directory name")?;
 
   let dst = Path::new(&settings.addons_dir).join(id);
   if dst.exists() {
+    if options.update_existing {
+      let id = id.to_str().context("Failed to convert id to string")?;
+      return update_addon(settings, id, package_dir, options.allow_downgrade);
+    }
+
     return Err(anyhow!(
       "Addon '{}' already exists in the addons directory",
       id.to_string_lossy()
     ));
   }
 
-  aeromod_fs::copy_dir_all(&addon_dir, &dst).context("Failed to copy addon files")?;
+  let staged = stage_package(settings, package_dir)?;
+  if let Err(e) = fs::rename(&staged, &dst) {
+    let _ = fs::remove_dir_all(&staged);
+    return Err(e).context("Failed to move addon into the addons directory");
+  }
 
   let id = id.to_str().context("Failed to convert id to string")?;
-  Ok(id.to_string())
+  if let Err(e) = baseline::record_baseline(settings, id) {
+    // Without a baseline, later modifications could not be detected.
+    let _ = fs::remove_dir_all(&dst);
+    return Err(e).context("Failed to record addon baseline");
+  }
+
+  Ok(())
+}
+
+/// Copies a package into the staging area next to the addons directory and
+/// checks it against its manifest and layout. The returned directory is on
+/// the same volume as the addons, so it can be moved into place with a
+/// single rename. Nothing is left behind if staging fails.
+fn stage_package(settings: &AppSettings, package_dir: &Path) -> Result<PathBuf> {
+  let id = package_dir
+    .file_name()
+    .context("Failed to get addon directory name")?;
+
+  let staging_dir = Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("staging");
+  fs::create_dir_all(&staging_dir)?;
+
+  let staged = staging_dir.join(id);
+  if staged.exists() {
+    fs::remove_dir_all(&staged).context("Failed to remove stale staging directory")?;
+  }
+
+  let result = aeromod_fs::copy_dir_all(package_dir, &staged)
+    .context("Failed to copy addon files")
+    .and_then(|_| verify_staged_package(&staged));
+
+  if let Err(e) = result {
+    let _ = fs::remove_dir_all(&staged);
+    return Err(e);
+  }
+
+  Ok(staged)
+}
+
+fn verify_staged_package(path: &Path) -> Result<()> {
+  Manifest::parse(path.join("manifest.json")).context("Failed to parse manifest.json")?;
+
+  let layout_path = path.join("layout.json");
+  if !layout_path.exists() {
+    return Ok(());
+  }
+
+  let result = verify_layout(path, &layout_path)?;
+  if let Some(node) = result
+    .files
+    .iter()
+    .find(|n| !matches!(n.status, VerificationStatus::Ok))
+  {
+    return Err(anyhow!(
+      "Copied file '{}' does not match layout.json",
+      node.path
+    ));
+  }
+
+  Ok(())
+}
+
+/// Replaces an installed addon with the package in `package_dir`.
+///
+/// The installed files are moved to a backup first and the community link is
+/// recreated afterwards, so the addon keeps its id, enabled state and preset
+/// membership. If any step fails, the backup is moved back in place. Unless
+/// `allow_downgrade` is set, the incoming version must be newer than the
+/// installed one.
+pub fn update_addon(
+  settings: &AppSettings,
+  id: &str,
+  package_dir: &Path,
+  allow_downgrade: bool,
+) -> Result<()> {
+  let installed = parse_addon(settings, id)?;
+  let incoming =
+    Manifest::parse(package_dir.join("manifest.json")).context("Failed to parse manifest.json")?;
+
+  if !allow_downgrade && incoming.package_version <= installed.version {
+    return Err(anyhow!(
+      "Addon '{}' version {} is not newer than the installed version {}",
+      id,
+      incoming.package_version,
+      installed.version
+    ));
+  }
+
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  let staged = stage_package(settings, package_dir)?;
+
+  let backup_dir = Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("backup");
+  let backup_path = backup_dir.join(id);
+  let prepared = fs::create_dir_all(&backup_dir)
+    .map_err(anyhow::Error::from)
+    .and_then(|_| {
+      if backup_path.exists() {
+        fs::remove_dir_all(&backup_path).context("Failed to remove stale backup")?;
+      }
+      if installed.enabled {
+        disable_addon(settings, id)?;
+      }
+      Ok(())
+    });
+  if let Err(e) = prepared {
+    let _ = fs::remove_dir_all(&staged);
+    return Err(e);
+  }
+
+  if let Err(e) = fs::rename(&addon_path, &backup_path) {
+    let _ = fs::remove_dir_all(&staged);
+    if installed.enabled {
+      let _ = enable_addon(settings, id);
+    }
+    return Err(e).context("Failed to back up installed addon");
+  }
+
+  let result = fs::rename(&staged, &addon_path)
+    .context("Failed to move addon into the addons directory")
+    .and_then(|_| Baseline::compute(&addon_path).context("Failed to record addon baseline"))
+    .and_then(|baseline| {
+      if installed.enabled {
+        enable_addon(settings, id)?;
+      }
+      Ok(baseline)
+    });
+
+  match result {
+    Ok(baseline) => {
+      fs::remove_dir_all(&backup_path).context("Failed to remove backup")?;
+      baseline.save(settings, id)
+    }
+    Err(e) => {
+      let _ = activation::deactivate(settings, id);
+      if staged.exists() {
+        let _ = fs::remove_dir_all(&staged);
+      }
+      if addon_path.exists() {
+        fs::remove_dir_all(&addon_path).context("Failed to remove partial update")?;
+      }
+      fs::rename(&backup_path, &addon_path).context("Failed to restore backup")?;
+      if installed.enabled {
+        enable_addon(settings, id)?;
+      }
+
+      Err(e.context(format!("Update of '{}' was rolled back", id)))
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackagePreviewStatus {
+  /// No addon with this id is installed.
+  New,
+  /// An older version is installed.
+  Upgrade,
+  /// The same version is installed.
+  Reinstall,
+  /// A newer version is installed.
+  Downgrade,
+  /// A directory with this id exists but is not a readable addon.
+  Conflict,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PackagePreview {
+  pub id: String,
+  pub name: String,
+  pub creator: String,
+  pub version: Version,
+  pub content_type: String,
+  pub size: u64,
+  pub status: PackagePreviewStatus,
+  pub installed_version: Option<Version>,
+  pub dependency_issues: Vec<DependencyIssue>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct InstallPreview {
+  pub packages: Vec<PackagePreview>,
+  pub total_size: u64,
+}
+
+/// Inspects an archive or folder and reports what installing it would do,
+/// without touching the addons directory.
+pub fn preview_install(settings: &AppSettings, src: &Path) -> Result<InstallPreview> {
+  if !src.exists() {
+    return Err(anyhow!("Directory '{}' does not exist", src.display()));
+  }
+
+  let tmp_guard = tempdir()?;
+
+  let search_dir = if src.is_dir() {
+    src.to_path_buf()
+  } else {
+    aeromod_fs::extract_archive(src, tmp_guard.path())?;
+    tmp_guard.path().to_path_buf()
+  };
+
+  let packages = find_packages(&search_dir)?;
+  if packages.is_empty() {
+    return Err(anyhow!("No manifest.json found"));
+  }
+
+  let manifests = packages
+    .iter()
+    .map(|package_dir| {
+      let id = package_dir
+        .file_name()
+        .context("Failed to get addon directory name")?
+        .to_string_lossy()
+        .into_owned();
+      let manifest = Manifest::parse(package_dir.join("manifest.json"))
+        .with_context(|| format!("Failed to parse manifest.json of '{}'", id))?;
+      Ok((id, package_dir, manifest))
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  let index = PackageIndex::load(settings)?;
+  let mut previews = Vec::new();
+
+  for (id, package_dir, manifest) in &manifests {
+    let installed_version = index.addon_version(id).cloned();
+    let status = match &installed_version {
+      Some(v) if *v < manifest.package_version => PackagePreviewStatus::Upgrade,
+      Some(v) if *v > manifest.package_version => PackagePreviewStatus::Downgrade,
+      Some(_) => PackagePreviewStatus::Reinstall,
+      None if settings.addons_dir.join(id).exists() => PackagePreviewStatus::Conflict,
+      None => PackagePreviewStatus::New,
+    };
+
+    // Dependencies shipped in the same archive are satisfied by it.
+    let dependency_issues = manifest
+      .dependencies
+      .iter()
+      .filter(|dep| {
+        !manifests
+          .iter()
+          .any(|(other, _, m)| *other == dep.name && m.package_version >= dep.package_version)
+      })
+      .filter_map(|dep| index.check_dependency(id, dep))
+      .collect();
+
+    previews.push(PackagePreview {
+      id: id.clone(),
+      name: manifest.title.clone(),
+      creator: manifest.creator.clone(),
+      version: manifest.package_version.clone(),
+      content_type: manifest.content_type.clone(),
+      size: aeromod_fs::get_directory_size(package_dir).unwrap_or(0),
+      status,
+      installed_version,
+      dependency_issues,
+    });
+  }
+
+  let total_size = previews.iter().map(|p| p.size).sum();
+
+  Ok(InstallPreview {
+    packages: previews,
+    total_size,
+  })
 }
 
 pub fn enable_addon(settings: &AppSettings, id: &str) -> Result<()> {
@@ -121,6 +570,11 @@ pub fn enable_addon(settings: &AppSettings, id: &str) -> Result<()> {
   }
 
   let target_path = Path::new(&settings.community_dir).join(id);
+  if target_path.is_symlink() && !target_path.exists() {
+    // A dangling link left behind by a moved or deleted addon.
+    aeromod_fs::remove_symlink_dir(&target_path).context("Failed to remove dangling symlink")?;
+  }
+
   if target_path.exists() {
     return Err(anyhow!(
       "Addon '{}' is already enabled or a file with that name exists in the community folder",
@@ -128,8 +582,7 @@ pub fn enable_addon(settings: &AppSettings, id: &str) -> Result<()> {
     ));
   }
 
-  aeromod_fs::symlink_dir(addon_path, target_path).context("Failed to create symlink")?;
-  Ok(())
+  activation::activate(settings, id)
 }
 
 pub fn disable_addon(settings: &AppSettings, id: &str) -> Result<()> {
@@ -138,16 +591,7 @@ pub fn disable_addon(settings: &AppSettings, id: &str) -> Result<()> {
     return Err(anyhow!("Addon '{}' not found in addons directory", id));
   }
 
-  let target_path = Path::new(&settings.community_dir).join(id);
-  if !target_path.exists() {
-    return Err(anyhow!(
-      "Addon '{}' is not enabled or does not exist in the community folder",
-      id
-    ));
-  }
-
-  aeromod_fs::remove_symlink_dir(target_path).context("Failed to remove symlink")?;
-  Ok(())
+  activation::deactivate(settings, id)
 }
 
 pub fn uninstall_addon(settings: &AppSettings, id: &str) -> Result<()> {
@@ -162,8 +606,8 @@ pub fn uninstall_addon(settings: &AppSettings, id: &str) -> Result<()> {
   }
 
   fs::remove_dir_all(addon_path)?;
-
-  Ok(())
+  remove_sidecars(settings, id)
+    .context("Addon was uninstalled but not all of its records could be removed")
 }
 
 pub fn rename_addon(settings: &AppSettings, id: &str, new_id: &str) -> Result<()> {
@@ -185,24 +629,61 @@ pub fn rename_addon(settings: &AppSettings, id: &str, new_id: &str) -> Result<()
   }
 
   fs::rename(old_path, new_path)?;
+  // The addon is usable under its new id even if some of its records stay
+  // behind, so it is enabled again before reporting that.
+  let moved = move_sidecars(settings, id, new_id);
 
   if was_enabled {
     enable_addon(settings, new_id)?;
   }
 
-  Ok(())
+  moved.context("Addon was renamed but not all of its records could be moved")
 }
 
-#[derive(Serialize, Deserialize)]
-struct Layout {
-  content: Vec<LayoutContent>,
+/// Files that AeroMod keeps about an addon outside of its package.
+fn sidecar_paths(settings: &AppSettings, id: &str) -> Vec<PathBuf> {
+  vec![
+    baseline::baseline_path(settings, id),
+    archive::retained_archive_path(settings, id),
+    provenance::provenance_path(settings, id),
+  ]
 }
 
-#[derive(Serialize, Deserialize)]
-struct LayoutContent {
-  path: String,
-  size: u64,
-  date: u64,
+/// Removes every record of an addon, continuing past failures. Returns the
+/// first error.
+fn remove_sidecars(settings: &AppSettings, id: &str) -> Result<()> {
+  let mut result = archive::release_archive(settings, id);
+  if settings.config_dir.is_some() {
+    result = result.and(MetadataStore::update(settings, |store| store.remove(id)));
+  }
+
+  for path in sidecar_paths(settings, id) {
+    if path.exists() {
+      result = result.and(fs::remove_file(path).context("Failed to remove addon metadata"));
+    }
+  }
+
+  result
+}
+
+/// Moves every record of an addon to `new_id`, continuing past failures.
+/// Returns the first error.
+fn move_sidecars(settings: &AppSettings, id: &str, new_id: &str) -> Result<()> {
+  let mut result = Ok(());
+  if settings.config_dir.is_some() {
+    result = MetadataStore::update(settings, |store| store.rename(id, new_id));
+  }
+
+  for (from, to) in sidecar_paths(settings, id)
+    .into_iter()
+    .zip(sidecar_paths(settings, new_id))
+  {
+    if from.exists() {
+      result = result.and(fs::rename(from, to).context("Failed to move addon metadata"));
+    }
+  }
+
+  result
 }
 
 #[derive(Serialize, Deserialize)]
@@ -223,6 +704,12 @@ pub enum VerificationStatus {
   Ok,
   SizeMismatch,
   NotFound,
+  /// The contents differ from the baseline recorded at install time.
+  Modified,
+  /// The file is neither in the layout nor in the baseline.
+  Extra,
+  /// The contents are unchanged but the modification time differs.
+  DateMismatch,
 }
 
 pub fn verify_addon(settings: &AppSettings, id: &str) -> Result<VerificationResult> {
@@ -236,6 +723,109 @@ pub fn verify_addon(settings: &AppSettings, id: &str) -> Result<VerificationResu
     return Err(anyhow!("Addon '{}' does not have a layout.json file", id));
   }
 
+  verify_layout(&addon_path, &layout_path)
+}
+
+/// Verifies an addon against both its `layout.json` and the checksum
+/// baseline recorded at install time, and reports files that are not part of
+/// the package. Modification times are compared against the baseline, or
+/// against the layout for addons installed without one. A date mismatch
+/// alone does not fail verification.
+pub fn verify_addon_deep(settings: &AppSettings, id: &str) -> Result<VerificationResult> {
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  if !addon_path.exists() {
+    return Err(anyhow!("Addon '{}' not found in addons directory", id));
+  }
+
+  let layout_path = addon_path.join("layout.json");
+  let layout: Option<Layout> = if layout_path.exists() {
+    Some(serde_json::from_reader(File::open(layout_path)?)?)
+  } else {
+    None
+  };
+  let baseline = Baseline::load(settings, id)?;
+
+  if layout.is_none() && baseline.is_none() {
+    return Err(anyhow!(
+      "Addon '{}' has neither a layout.json file nor a recorded baseline",
+      id
+    ));
+  }
+
+  // Expected size and date per file, plus the baseline checksum if any.
+  let mut expected: BTreeMap<&str, (u64, u64, Option<&str>)> = BTreeMap::new();
+  for content in layout.iter().flat_map(|l| &l.content) {
+    expected.insert(&content.path, (content.size, content.date, None));
+  }
+  for (path, entry) in baseline.iter().flat_map(|b| &b.files) {
+    let size = expected.get(path.as_str()).map_or(entry.size, |e| e.0);
+    expected.insert(path, (size, entry.date, Some(&entry.sha256)));
+  }
+
+  let mut verified = true;
+  let mut files = Vec::new();
+
+  for (path, (size, date, sha256)) in &expected {
+    let file_path = addon_path.join(path);
+    let Ok(metadata) = fs::metadata(&file_path) else {
+      verified = false;
+      files.push(VerificationNode {
+        status: VerificationStatus::NotFound,
+        path: path.to_string(),
+        size: *size,
+      });
+      continue;
+    };
+
+    let status = if metadata.len() != *size {
+      VerificationStatus::SizeMismatch
+    } else if sha256
+      .is_some_and(|hash| baseline::hash_file(&file_path).ok().as_deref() != Some(hash))
+    {
+      VerificationStatus::Modified
+    } else if layout::file_time(&metadata) != *date {
+      VerificationStatus::DateMismatch
+    } else {
+      VerificationStatus::Ok
+    };
+
+    if matches!(
+      status,
+      VerificationStatus::SizeMismatch | VerificationStatus::Modified
+    ) {
+      verified = false;
+    }
+
+    files.push(VerificationNode {
+      status,
+      path: path.to_string(),
+      size: metadata.len(),
+    });
+  }
+
+  for entry in WalkDir::new(&addon_path).min_depth(1).sort_by_file_name() {
+    let entry = entry?;
+    if !entry.file_type().is_file() {
+      continue;
+    }
+
+    let path = baseline::relative_path(&addon_path, entry.path())?;
+    if expected.contains_key(path.as_str()) || layout::EXCLUDED_FILES.contains(&path.as_str()) {
+      continue;
+    }
+
+    verified = false;
+    files.push(VerificationNode {
+      status: VerificationStatus::Extra,
+      path,
+      size: entry.metadata()?.len(),
+    });
+  }
+
+  Ok(VerificationResult { verified, files })
+}
+
+fn verify_layout(addon_path: &Path, layout_path: &Path) -> Result<VerificationResult> {
   let file = File::open(layout_path)?;
   let layout: Layout = serde_json::from_reader(file)?;
 
@@ -294,3 +884,464 @@ pub fn get_addon_thumbnail(settings: &AppSettings, id: &str) -> Result<String> {
 
   Ok(format!("data:image/jpg;base64,{}", base64_data))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{write_package, write_versioned_package};
+  use std::time::{Duration, UNIX_EPOCH};
+
+  #[test]
+  fn test_scan_addons_reports_unrecognized_entries() {
+    let (_dir, settings) = crate::testing::settings();
+    write_package(&settings.addons_dir.join("valid-addon"), "Valid");
+
+    let broken = settings.addons_dir.join("broken-addon");
+    fs::create_dir_all(&broken).unwrap();
+    fs::write(broken.join("manifest.json"), r#"{"title": "Broken""#).unwrap();
+
+    fs::create_dir_all(settings.addons_dir.join("no-manifest")).unwrap();
+
+    let mut library = scan_addons(&settings).unwrap();
+    library.unrecognized.sort_by(|a, b| a.id.cmp(&b.id));
+
+    assert_eq!(library.addons.len(), 1);
+    assert_eq!(library.addons[0].name, "Valid");
+    assert_eq!(library.unrecognized.len(), 2);
+    assert_eq!(library.unrecognized[0].id, "broken-addon");
+    assert!(library.unrecognized[0].error.contains("EOF"));
+    assert_eq!(library.unrecognized[1].id, "no-manifest");
+  }
+
+  #[test]
+  fn test_install_addon_installs_every_package() {
+    let (dir, settings) = crate::testing::settings();
+    let bundle = dir.path().join("bundle");
+    write_package(&bundle.join("Airport").join("airport-ltfm"), "Airport");
+    write_package(&bundle.join("Library").join("ltfm-library"), "Library");
+    // Nested manifests inside a package are part of that package.
+    write_package(
+      &bundle.join("Library").join("ltfm-library").join("extras"),
+      "Extras",
+    );
+    write_package(&settings.addons_dir.join("ltfm-library"), "Existing");
+
+    let results = install_addon(&settings, &bundle).unwrap();
+    let ids: Vec<_> = results.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(ids, ["airport-ltfm", "ltfm-library"]);
+    assert!(results[0].result.is_ok());
+    assert!(results[1].result.is_err());
+    assert!(
+      settings
+        .addons_dir
+        .join("airport-ltfm")
+        .join("manifest.json")
+        .exists()
+    );
+  }
+
+  #[test]
+  fn test_preview_install_does_not_touch_addons_dir() {
+    let (dir, settings) = crate::testing::settings();
+    write_versioned_package(
+      &settings.addons_dir.join("airport-ltfm"),
+      "Airport",
+      "1.0.0",
+      &[],
+    );
+
+    let bundle = dir.path().join("bundle");
+    write_versioned_package(
+      &bundle.join("airport-ltfm"),
+      "Airport",
+      "1.1.0",
+      &[("ltfm-library", "1.0.0"), ("missing-library", "1.0.0")],
+    );
+    write_versioned_package(&bundle.join("ltfm-library"), "Library", "1.0.0", &[]);
+
+    let preview = preview_install(&settings, &bundle).unwrap();
+    assert_eq!(preview.packages.len(), 2);
+
+    let airport = &preview.packages[0];
+    assert_eq!(airport.status, PackagePreviewStatus::Upgrade);
+    assert_eq!(airport.installed_version, Some(Version::parse("1.0.0")));
+    assert_eq!(airport.dependency_issues.len(), 1);
+    assert_eq!(airport.dependency_issues[0].dependency, "missing-library");
+
+    assert_eq!(preview.packages[1].status, PackagePreviewStatus::New);
+    assert!(preview.total_size > 0);
+    assert!(!settings.addons_dir.join("ltfm-library").exists());
+  }
+
+  #[test]
+  fn test_update_addon_keeps_enabled_state() {
+    let (dir, settings) = crate::testing::settings();
+    let installed = settings.addons_dir.join("airport-ltfm");
+    write_versioned_package(&installed, "Airport", "1.0.0", &[]);
+    fs::write(installed.join("old.bgl"), "old").unwrap();
+    enable_addon(&settings, "airport-ltfm").unwrap();
+
+    let incoming = dir.path().join("incoming").join("airport-ltfm");
+    write_versioned_package(&incoming, "Airport", "0.9.0", &[]);
+    assert!(update_addon(&settings, "airport-ltfm", &incoming, false).is_err());
+
+    write_versioned_package(&incoming, "Airport", "1.1.0", &[]);
+    fs::write(incoming.join("new.bgl"), "new").unwrap();
+    update_addon(&settings, "airport-ltfm", &incoming, false).unwrap();
+
+    let addon = parse_addon(&settings, "airport-ltfm").unwrap();
+    assert_eq!(addon.version, Version::parse("1.1.0"));
+    assert!(addon.enabled);
+    assert!(installed.join("new.bgl").exists());
+    assert!(!installed.join("old.bgl").exists());
+    assert!(
+      !settings
+        .addons_dir
+        .join(INTERNAL_DIR_NAME)
+        .join("backup")
+        .join("airport-ltfm")
+        .exists()
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_update_addon_rolls_back_on_failure() {
+    let (dir, settings) = crate::testing::settings();
+    let installed = settings.addons_dir.join("airport-ltfm");
+    write_versioned_package(&installed, "Airport", "1.0.0", &[]);
+    fs::write(installed.join("old.bgl"), "old").unwrap();
+    enable_addon(&settings, "airport-ltfm").unwrap();
+
+    let incoming = dir.path().join("incoming").join("airport-ltfm");
+    write_versioned_package(&incoming, "Airport", "1.1.0", &[]);
+    // A dangling link makes the copy fail halfway through.
+    std::os::unix::fs::symlink(dir.path().join("missing"), incoming.join("broken.bgl")).unwrap();
+
+    assert!(update_addon(&settings, "airport-ltfm", &incoming, false).is_err());
+
+    let addon = parse_addon(&settings, "airport-ltfm").unwrap();
+    assert_eq!(addon.version, Version::parse("1.0.0"));
+    assert!(addon.enabled);
+    assert!(installed.join("old.bgl").exists());
+    assert_eq!(scan_addons(&settings).unwrap().unrecognized.len(), 0);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_install_addon_leaves_nothing_behind_on_failure() {
+    let (dir, settings) = crate::testing::settings();
+    let src = dir.path().join("download");
+    write_package(&src.join("airport-ltfm"), "Airport");
+    std::os::unix::fs::symlink(
+      dir.path().join("missing"),
+      src.join("airport-ltfm").join("broken.bgl"),
+    )
+    .unwrap();
+
+    let installs = install_addon(&settings, &src).unwrap();
+    assert!(installs[0].result.is_err());
+    assert!(!settings.addons_dir.join("airport-ltfm").exists());
+    assert!(
+      fs::read_dir(settings.addons_dir.join(INTERNAL_DIR_NAME).join("staging"))
+        .unwrap()
+        .next()
+        .is_none()
+    );
+    assert!(scan_addons(&settings).unwrap().addons.is_empty());
+  }
+
+  #[test]
+  fn test_install_addon_rejects_package_not_matching_layout() {
+    let (dir, settings) = crate::testing::settings();
+    let package = dir.path().join("download").join("airport-ltfm");
+    write_package(&package, "Airport");
+    fs::write(package.join("scenery.bgl"), "bgl").unwrap();
+    let layout = serde_json::json!({
+      "content": [{ "path": "scenery.bgl", "size": 1024, "date": 0 }]
+    });
+    fs::write(package.join("layout.json"), layout.to_string()).unwrap();
+
+    let installs = install_addon(&settings, &package).unwrap();
+    let error = installs[0].result.as_ref().err().unwrap();
+    assert!(error.to_string().contains("scenery.bgl"));
+    assert!(!settings.addons_dir.join("airport-ltfm").exists());
+  }
+
+  #[test]
+  fn test_verify_addon_deep_detects_tampering() {
+    let (dir, settings) = crate::testing::settings();
+    let package = dir.path().join("download").join("airport-ltfm");
+    write_package(&package, "Airport");
+    fs::write(package.join("a.bgl"), "aaaa").unwrap();
+    fs::write(package.join("b.bgl"), "bbbb").unwrap();
+    layout::rebuild_layout(&package).unwrap();
+
+    install_addon(&settings, &package).unwrap()[0]
+      .result
+      .as_ref()
+      .unwrap();
+    let result = verify_addon_deep(&settings, "airport-ltfm").unwrap();
+    assert!(result.verified);
+
+    // Rewriting manifest.json and layout.json is not a modification.
+    layout::rebuild_addon_layout(&settings, "airport-ltfm").unwrap();
+    let result = verify_addon_deep(&settings, "airport-ltfm").unwrap();
+    assert!(result.verified);
+
+    let installed = settings.addons_dir.join("airport-ltfm");
+    fs::write(installed.join("a.bgl"), "AAAA").unwrap();
+    fs::remove_file(installed.join("b.bgl")).unwrap();
+    fs::write(installed.join("c.bgl"), "cccc").unwrap();
+
+    let result = verify_addon_deep(&settings, "airport-ltfm").unwrap();
+    assert!(!result.verified);
+    let statuses: Vec<_> = result
+      .files
+      .iter()
+      .filter(|n| !matches!(n.status, VerificationStatus::Ok))
+      .map(|n| (n.path.as_str(), serde_json::to_string(&n.status).unwrap()))
+      .collect();
+    assert_eq!(
+      statuses,
+      [
+        ("a.bgl", "\"Modified\"".to_string()),
+        ("b.bgl", "\"NotFound\"".to_string()),
+        ("c.bgl", "\"Extra\"".to_string()),
+      ]
+    );
+
+    rename_addon(&settings, "airport-ltfm", "airport-ltfm-old").unwrap();
+    assert!(
+      Baseline::load(&settings, "airport-ltfm-old")
+        .unwrap()
+        .is_some()
+    );
+    uninstall_addon(&settings, "airport-ltfm-old").unwrap();
+    assert!(
+      Baseline::load(&settings, "airport-ltfm-old")
+        .unwrap()
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn test_repair_addon_from_retained_archive() {
+    let (dir, mut settings) = crate::testing::settings();
+    settings.retain_archives = true;
+
+    let package = dir
+      .path()
+      .join("download")
+      .join("Airport")
+      .join("airport-ltfm");
+    write_package(&package, "Airport");
+    fs::create_dir_all(package.join("scenery")).unwrap();
+    fs::write(package.join("scenery").join("a.bgl"), "aaaa").unwrap();
+    fs::write(package.join("b.bgl"), "bbbb").unwrap();
+    layout::rebuild_layout(&package).unwrap();
+
+    // The comment makes archives with the same contents differ.
+    let write_zip = |path: &Path, comment: &str| {
+      let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+      zip.set_comment(comment);
+      for entry in WalkDir::new(dir.path().join("download")).min_depth(1) {
+        let entry = entry.unwrap();
+        if entry.file_type().is_file() {
+          let name = baseline::relative_path(&dir.path().join("download"), entry.path()).unwrap();
+          zip
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+          std::io::Write::write_all(&mut zip, &fs::read(entry.path()).unwrap()).unwrap();
+        }
+      }
+      zip.finish().unwrap();
+    };
+    let archive_path = dir.path().join("airport.zip");
+    write_zip(&archive_path, "1");
+
+    install_addon(&settings, &archive_path).unwrap()[0]
+      .result
+      .as_ref()
+      .unwrap();
+    // Installing the same download again does not store a second copy.
+    archive::retain_archive(&settings, &archive_path).unwrap();
+    let library = settings.addons_dir.join(INTERNAL_DIR_NAME).join("archives");
+    assert_eq!(fs::read_dir(&library).unwrap().count(), 1);
+
+    // Reinstalling from another download replaces the retained archive.
+    let update_path = dir.path().join("airport-update.zip");
+    write_zip(&update_path, "2");
+    let options = InstallOptions {
+      update_existing: true,
+      allow_downgrade: true,
+      ..Default::default()
+    };
+    install_addon_with(&settings, &update_path, &options).unwrap()[0]
+      .result
+      .as_ref()
+      .unwrap();
+    let retained = RetainedArchive::load(&settings, "airport-ltfm")
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      fs::read_dir(&library)
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect::<Vec<_>>(),
+      [std::ffi::OsString::from(retained.archive)]
+    );
+
+    let installed = settings.addons_dir.join("airport-ltfm");
+    fs::remove_file(installed.join("scenery").join("a.bgl")).unwrap();
+    fs::write(installed.join("b.bgl"), "b").unwrap();
+
+    let repaired = archive::repair_addon(&settings, "airport-ltfm").unwrap();
+    assert_eq!(repaired, ["b.bgl", "scenery/a.bgl"]);
+    assert!(verify_addon(&settings, "airport-ltfm").unwrap().verified);
+    assert!(
+      archive::repair_addon(&settings, "airport-ltfm")
+        .unwrap()
+        .is_empty()
+    );
+
+    // Paths that lead outside of the addon are refused.
+    let mut layout: layout::Layout =
+      serde_json::from_str(&fs::read_to_string(installed.join("layout.json")).unwrap()).unwrap();
+    layout.content.push(layout::LayoutContent {
+      path: "../outside.bgl".to_string(),
+      size: 4,
+      date: 0,
+    });
+    fs::write(
+      installed.join("layout.json"),
+      serde_json::to_string(&layout).unwrap(),
+    )
+    .unwrap();
+    assert!(archive::repair_addon(&settings, "airport-ltfm").is_err());
+    assert!(!settings.addons_dir.join("outside.bgl").exists());
+
+    // The archive is dropped with the last addon installed from it.
+    uninstall_addon(&settings, "airport-ltfm").unwrap();
+    assert_eq!(fs::read_dir(&library).unwrap().count(), 0);
+  }
+
+  #[test]
+  fn test_metadata_follows_rename_and_uninstall() {
+    let (dir, settings) = crate::testing::settings();
+    let package = dir.path().join("download").join("airport-ltfm");
+    write_versioned_package(&package, "Airport", "1.0.0", &[]);
+    install_addon(&settings, &package).unwrap();
+
+    let metadata = AddonMetadata {
+      tags: vec!["EU".into()],
+      ..Default::default()
+    };
+    MetadataStore::update(&settings, |store| {
+      store.set("airport-ltfm", metadata.clone())
+    })
+    .unwrap();
+    assert_eq!(get_addons(&settings).unwrap()[0].metadata, metadata);
+
+    rename_addon(&settings, "airport-ltfm", "airport-ltfm-old").unwrap();
+    assert_eq!(
+      parse_addon(&settings, "airport-ltfm-old").unwrap().metadata,
+      metadata
+    );
+
+    uninstall_addon(&settings, "airport-ltfm-old").unwrap();
+    assert!(MetadataStore::open(&settings).unwrap().tags().is_empty());
+
+    // An unreadable store is reported without undoing the rename or leaving
+    // the addon disabled.
+    install_addon(&settings, &package).unwrap();
+    enable_addon(&settings, "airport-ltfm").unwrap();
+    let store_path = settings.config_dir.as_ref().unwrap().join("metadata.json");
+    fs::write(&store_path, "{").unwrap();
+
+    assert!(rename_addon(&settings, "airport-ltfm", "airport-ltfm-old").is_err());
+    assert!(parse_addon(&settings, "airport-ltfm-old").unwrap().enabled);
+    assert!(uninstall_addon(&settings, "airport-ltfm-old").is_err());
+    assert!(!settings.addons_dir.join("airport-ltfm-old").exists());
+  }
+
+  #[test]
+  fn test_install_records_provenance() {
+    let (dir, settings) = crate::testing::settings();
+    let package = dir.path().join("download").join("airport-ltfm");
+    write_versioned_package(&package, "Airport", "1.0.0", &[]);
+    install_addon(&settings, &package).unwrap();
+
+    let provenance = parse_addon(&settings, "airport-ltfm")
+      .unwrap()
+      .provenance
+      .unwrap();
+    assert_eq!(provenance.source_file, "airport-ltfm");
+    assert!(provenance.archive_hash.is_none());
+    assert!(provenance.previous_version.is_none());
+
+    write_versioned_package(&package, "Airport", "1.1.0", &[]);
+    let options = InstallOptions {
+      update_existing: true,
+      installer_version: Some("1.2.0".to_string()),
+      ..Default::default()
+    };
+    install_addon_with(&settings, &package, &options).unwrap();
+
+    let provenance = Provenance::load(&settings, "airport-ltfm")
+      .unwrap()
+      .unwrap();
+    assert_eq!(provenance.previous_version, Some(Version::parse("1.0.0")));
+    assert_eq!(provenance.installer_version.as_deref(), Some("1.2.0"));
+  }
+
+  #[test]
+  fn test_scan_addons_reuses_index_until_modified() {
+    let (_dir, settings) = crate::testing::settings();
+    for id in ["airport-ltfm", "airport-eddf", "airport-kjfk"] {
+      write_versioned_package(&settings.addons_dir.join(id), id, "1.0.0", &[]);
+    }
+    let package = settings.addons_dir.join("airport-ltfm");
+    fs::create_dir_all(package.join("scenery")).unwrap();
+    fs::write(package.join("layout.json"), r#"{"content": []}"#).unwrap();
+
+    let library = scan_addons(&settings).unwrap();
+    assert_eq!(library.addons.len(), 3);
+    assert!(
+      settings
+        .addons_dir
+        .join(INTERNAL_DIR_NAME)
+        .join("library.json")
+        .exists()
+    );
+
+    let set_modified = |path: &Path, secs: u64| {
+      File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+        .unwrap();
+    };
+    let find = |library: &AddonLibrary| {
+      library
+        .addons
+        .iter()
+        .find(|a| a.id == "airport-ltfm")
+        .map(|a| (a.name.clone(), a.version.clone(), a.size))
+        .unwrap()
+    };
+
+    write_versioned_package(&package, "Istanbul", "1.1.0", &[]);
+    set_modified(&package.join("manifest.json"), 1_000_000);
+    let (name, version, size) = find(&scan_addons(&settings).unwrap());
+    assert_eq!(name, "Istanbul");
+    assert_eq!(version, Version::parse("1.1.0"));
+
+    // A file added below the top level is picked up once layout.json changes.
+    fs::write(package.join("scenery").join("a.bgl"), [0; 100]).unwrap();
+    assert_eq!(find(&scan_addons(&settings).unwrap()).2, size);
+    set_modified(&package.join("layout.json"), 1_000_000);
+    assert_eq!(find(&scan_addons(&settings).unwrap()).2, size + 100);
+  }
+}
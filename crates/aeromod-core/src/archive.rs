@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use tempfile::tempdir;
+
+use crate::activation;
+use crate::addon::{self, INTERNAL_DIR_NAME, VerificationStatus};
+use crate::baseline;
+use crate::library::LibraryIndex;
+
+/// Records which archive in the archive library an addon was installed from.
+#[derive(Serialize, Deserialize)]
+pub struct RetainedArchive {
+  /// File name of the archive inside the archive library.
+  pub archive: String,
+  /// Path of the package folder inside the archive, `/` separated. Empty if
+  /// the package is at the root of the archive.
+  pub package_root: String,
+}
+
+impl RetainedArchive {
+  pub fn load(settings: &AppSettings, id: &str) -> Result<Option<Self>> {
+    let path = retained_archive_path(settings, id);
+    if !path.exists() {
+      return Ok(None);
+    }
+
+    let file = File::open(path)?;
+    Ok(Some(serde_json::from_reader(BufReader::new(file))?))
+  }
+
+  /// Stores the record, releasing the archive of a previous record of the
+  /// addon.
+  pub fn save(&self, settings: &AppSettings, id: &str) -> Result<()> {
+    let previous = Self::load(settings, id).ok().flatten();
+
+    let path = retained_archive_path(settings, id);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    aeromod_fs::write_atomic(path, |writer| {
+      serde_json::to_writer(writer, self).map_err(Into::into)
+    })
+    .context("Failed to write retained archive record")?;
+
+    if let Some(previous) = previous.filter(|p| p.archive != self.archive) {
+      remove_unreferenced(settings, &previous.archive)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Removes the retained archive record of an addon, and the archive itself
+/// once no other addon was installed from it.
+pub(crate) fn release_archive(settings: &AppSettings, id: &str) -> Result<()> {
+  let Some(retained) = RetainedArchive::load(settings, id)? else {
+    return Ok(());
+  };
+
+  fs::remove_file(retained_archive_path(settings, id))
+    .context("Failed to remove retained archive record")?;
+  remove_unreferenced(settings, &retained.archive)
+}
+
+/// Deletes an archive from the library unless a retained archive record still
+/// refers to it. Records that cannot be read keep the archive.
+fn remove_unreferenced(settings: &AppSettings, archive: &str) -> Result<()> {
+  if let Ok(entries) = fs::read_dir(sources_dir(settings)) {
+    for entry in entries.flatten() {
+      let referenced = File::open(entry.path())
+        .ok()
+        .and_then(|file| serde_json::from_reader::<_, RetainedArchive>(BufReader::new(file)).ok())
+        .is_none_or(|r| r.archive == archive);
+      if referenced {
+        return Ok(());
+      }
+    }
+  }
+
+  let path = library_dir(settings).join(archive);
+  if path.exists() {
+    fs::remove_file(path).context("Failed to remove retained archive")?;
+  }
+
+  Ok(())
+}
+
+fn library_dir(settings: &AppSettings) -> PathBuf {
+  Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("archives")
+}
+
+fn sources_dir(settings: &AppSettings) -> PathBuf {
+  Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("sources")
+}
+
+pub(crate) fn retained_archive_path(settings: &AppSettings, id: &str) -> PathBuf {
+  sources_dir(settings).join(format!("{}.json", id))
+}
+
+/// Copies an archive into the archive library unless an archive with the
+/// same contents is already stored there, and returns its file name in the
+/// library. Archives are named after their SHA-256 so that the same download
+/// is only kept once.
+pub fn retain_archive(settings: &AppSettings, src: &Path) -> Result<String> {
+  let hash = baseline::hash_file(src)?;
+  store_archive(settings, src, &hash)
+}
+
+pub(crate) fn store_archive(settings: &AppSettings, src: &Path, hash: &str) -> Result<String> {
+  let name = match src.extension() {
+    Some(ext) => format!("{}.{}", hash, ext.to_string_lossy().to_lowercase()),
+    None => hash.to_string(),
+  };
+
+  let dir = library_dir(settings);
+  fs::create_dir_all(&dir)?;
+
+  let dst = dir.join(&name);
+  if !dst.exists() {
+    let mut file = File::open(src)?;
+    aeromod_fs::write_atomic(&dst, |writer| io::copy(&mut file, writer).map(|_| ()))
+      .context("Failed to copy archive into the library")?;
+  }
+
+  Ok(name)
+}
+
+/// Restores the files of an addon that are missing or whose size differs
+/// from `layout.json` by extracting only those files again from the archive
+/// the addon was installed from, and refreshes a hard-linked or copied addon
+/// in the community folder. Returns the paths of the restored files.
+pub fn repair_addon(settings: &AppSettings, id: &str) -> Result<Vec<String>> {
+  let verification = addon::verify_addon(settings, id)?;
+  let damaged: Vec<_> = verification
+    .files
+    .into_iter()
+    .filter(|n| {
+      matches!(
+        n.status,
+        VerificationStatus::NotFound | VerificationStatus::SizeMismatch
+      )
+    })
+    .map(|n| n.path)
+    .collect();
+
+  if damaged.is_empty() {
+    return Ok(damaged);
+  }
+
+  // Paths come from layout.json and must not lead outside of the addon.
+  if let Some(path) = damaged
+    .iter()
+    .find(|p| !aeromod_fs::is_enclosed(Path::new(p)))
+  {
+    return Err(anyhow!("Invalid path '{}' in layout.json", path));
+  }
+
+  let retained = RetainedArchive::load(settings, id)?
+    .ok_or_else(|| anyhow!("Addon '{}' was not installed from a retained archive", id))?;
+  let archive_path = library_dir(settings).join(&retained.archive);
+  if !archive_path.exists() {
+    return Err(anyhow!(
+      "Archive '{}' is missing from the archive library",
+      retained.archive
+    ));
+  }
+
+  let package_root = Path::new(&retained.package_root);
+  let wanted: HashSet<PathBuf> = damaged.iter().map(|p| package_root.join(p)).collect();
+
+  let tmp_dir = tempdir()?;
+  aeromod_fs::extract_archive_entries(&archive_path, tmp_dir.path(), |path| wanted.contains(path))?;
+  let package_dir = tmp_dir.path().join(package_root);
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+
+  for path in &damaged {
+    let src = package_dir.join(path);
+    if !src.is_file() {
+      return Err(anyhow!("File '{}' is not in the original archive", path));
+    }
+
+    let dst = addon_path.join(path);
+    if let Some(parent) = dst.parent() {
+      fs::create_dir_all(parent)?;
+    }
+    fs::copy(&src, &dst).with_context(|| format!("Failed to restore '{}'", path))?;
+  }
+  LibraryIndex::invalidate(settings, id)?;
+  activation::refresh(settings, id)?;
+
+  Ok(damaged)
+}
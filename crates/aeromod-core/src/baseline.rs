@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use walkdir::WalkDir;
+
+use crate::addon::INTERNAL_DIR_NAME;
+use crate::layout;
+
+/// Checksums of every file of an addon, recorded when it was installed so
+/// that later modifications can be detected. Stored outside of the package in
+/// the internal directory of the addons directory. `manifest.json` and
+/// `layout.json` are left out, since AeroMod rewrites them itself when the
+/// layout is rebuilt.
+#[derive(Serialize, Deserialize)]
+pub struct Baseline {
+  /// Files keyed by their path relative to the package root, using `/` as
+  /// separator like `layout.json`.
+  pub files: BTreeMap<String, BaselineEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BaselineEntry {
+  pub size: u64,
+  /// Modification time as a Windows FILETIME.
+  pub date: u64,
+  /// Hex encoded SHA-256 of the file contents.
+  pub sha256: String,
+}
+
+impl Baseline {
+  pub fn compute<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let path = path.as_ref();
+    let mut files = BTreeMap::new();
+
+    for entry in WalkDir::new(path).min_depth(1) {
+      let entry = entry?;
+      if !entry.file_type().is_file() {
+        continue;
+      }
+
+      let relative = relative_path(path, entry.path())?;
+      if layout::EXCLUDED_FILES.contains(&relative.as_str()) {
+        continue;
+      }
+
+      let metadata = entry.metadata()?;
+      files.insert(
+        relative,
+        BaselineEntry {
+          size: metadata.len(),
+          date: layout::file_time(&metadata),
+          sha256: hash_file(entry.path())?,
+        },
+      );
+    }
+
+    Ok(Self { files })
+  }
+
+  pub fn load(settings: &AppSettings, id: &str) -> Result<Option<Self>> {
+    let path = baseline_path(settings, id);
+    if !path.exists() {
+      return Ok(None);
+    }
+
+    let file = File::open(path)?;
+    let mut baseline: Self = serde_json::from_reader(BufReader::new(file))?;
+    // Baselines recorded before the package files were left out.
+    baseline
+      .files
+      .retain(|path, _| !layout::EXCLUDED_FILES.contains(&path.as_str()));
+    Ok(Some(baseline))
+  }
+
+  pub fn save(&self, settings: &AppSettings, id: &str) -> Result<()> {
+    let path = baseline_path(settings, id);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    aeromod_fs::write_atomic(path, |writer| {
+      serde_json::to_writer(writer, self).map_err(Into::into)
+    })
+    .context("Failed to write addon baseline")
+  }
+}
+
+/// Computes and stores the baseline of an installed addon.
+pub fn record_baseline(settings: &AppSettings, id: &str) -> Result<()> {
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  Baseline::compute(addon_path)?.save(settings, id)
+}
+
+pub(crate) fn baseline_path(settings: &AppSettings, id: &str) -> PathBuf {
+  Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("baselines")
+    .join(format!("{}.json", id))
+}
+
+pub(crate) fn relative_path(root: &Path, path: &Path) -> Result<String> {
+  let relative = path.strip_prefix(root)?;
+  Ok(
+    relative
+      .components()
+      .map(|c| c.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>()
+      .join("/"),
+  )
+}
+
+pub(crate) fn hash_file<P: AsRef<Path>>(path: P) -> Result<String> {
+  let mut file = File::open(path)?;
+  let mut hasher = Sha256::new();
+  io::copy(&mut file, &mut hasher)?;
+  Ok(format!("{:x}", hasher.finalize()))
+}
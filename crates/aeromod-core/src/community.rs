@@ -0,0 +1,526 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::activation;
+use crate::addon::INTERNAL_DIR_NAME;
+use crate::baseline;
+use crate::manifest::Manifest;
+use crate::version::Version;
+
+/// A package placed directly in the community folder instead of being linked
+/// from the addons directory.
+#[derive(Serialize, Deserialize)]
+pub struct UnmanagedPackage {
+  pub id: String,
+  pub name: String,
+  pub creator: String,
+  pub version: Version,
+  pub content_type: String,
+  pub size: u64,
+  /// An addon with the same id already exists in the addons directory, so the
+  /// package cannot be imported as is.
+  pub conflict: bool,
+}
+
+/// Lists the real package directories in the community folder.
+pub fn find_unmanaged(settings: &AppSettings) -> Result<Vec<UnmanagedPackage>> {
+  let mut packages = Vec::new();
+
+  for entry in fs::read_dir(&settings.community_dir)? {
+    let entry = entry?;
+    if !entry.file_type()?.is_dir() {
+      continue;
+    }
+
+    let path = entry.path();
+    if activation::is_managed_copy(&path) || is_temp_name(&entry.file_name().to_string_lossy()) {
+      continue;
+    }
+
+    let Ok(manifest) = Manifest::parse(path.join("manifest.json")) else {
+      continue;
+    };
+
+    let id = entry.file_name().to_string_lossy().into_owned();
+    packages.push(UnmanagedPackage {
+      conflict: Path::new(&settings.addons_dir).join(&id).exists(),
+      id,
+      name: manifest.title,
+      creator: manifest.creator,
+      version: manifest.package_version,
+      content_type: manifest.content_type,
+      size: aeromod_fs::get_directory_size(&path).unwrap_or(0),
+    });
+  }
+
+  packages.sort_by(|a, b| a.id.cmp(&b.id));
+  Ok(packages)
+}
+
+/// Moves an unmanaged package from the community folder into the addons
+/// directory and enables it again using the activation mode of the library,
+/// so it stays enabled under the same name.
+pub fn import_unmanaged(settings: &AppSettings, id: &str) -> Result<()> {
+  let community_path = Path::new(&settings.community_dir).join(id);
+  let is_dir = fs::symlink_metadata(&community_path)
+    .map(|m| m.is_dir())
+    .unwrap_or(false);
+  if !is_dir || activation::is_managed_copy(&community_path) {
+    return Err(anyhow!(
+      "Package '{}' is not an unmanaged folder in the community folder",
+      id
+    ));
+  }
+
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  if addon_path.exists() {
+    return Err(anyhow!(
+      "Addon '{}' already exists in the addons directory",
+      id
+    ));
+  }
+
+  // On another volume the package is copied instead, and the original is
+  // only set aside until the link is in place so that it can be restored.
+  let set_aside = match fs::rename(&community_path, &addon_path) {
+    Ok(()) => None,
+    Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+      copy_into_addons_dir(settings, id, &community_path, &addon_path)?;
+
+      let set_aside = community_path.with_file_name(temp_name(id, "import"));
+      if let Err(e) = fs::rename(&community_path, &set_aside) {
+        let _ = fs::remove_dir_all(&addon_path);
+        return Err(e).context("Failed to set the original package aside");
+      }
+      Some(set_aside)
+    }
+    Err(e) => return Err(e).context("Failed to move package into the addons directory"),
+  };
+
+  if let Err(e) = activation::activate(settings, id) {
+    // Put the package back where the sim expects it.
+    match set_aside {
+      Some(set_aside) => {
+        fs::rename(&set_aside, &community_path).with_context(|| {
+          format!(
+            "Failed to restore the original package from '{}'",
+            set_aside.display()
+          )
+        })?;
+        fs::remove_dir_all(&addon_path)?;
+      }
+      None => fs::rename(&addon_path, &community_path)
+        .context("Failed to move the package back into the community folder")?,
+    }
+    return Err(e);
+  }
+
+  if let Some(set_aside) = set_aside {
+    fs::remove_dir_all(&set_aside).with_context(|| {
+      format!(
+        "Package was imported but the original could not be removed from '{}'",
+        set_aside.display()
+      )
+    })?;
+  }
+
+  // The import itself succeeded; without a baseline only modifications of
+  // the addon cannot be detected later.
+  if let Err(e) = baseline::record_baseline(settings, id) {
+    eprintln!("Failed to record the baseline of '{}': {:#}", id, e);
+  }
+
+  Ok(())
+}
+
+/// Name of a directory AeroMod creates next to `id` in the community folder
+/// while it replaces it.
+pub(crate) fn temp_name(id: &str, purpose: &str) -> String {
+  format!(".{}.aeromod-{}", id, purpose)
+}
+
+fn is_temp_name(name: &str) -> bool {
+  name.starts_with('.') && name.contains(".aeromod-")
+}
+
+/// Copies a package into the addons directory through the staging area, so
+/// that a failed copy never leaves a partial addon behind.
+fn copy_into_addons_dir(settings: &AppSettings, id: &str, src: &Path, dst: &Path) -> Result<()> {
+  let staged = Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("staging")
+    .join(id);
+  if staged.exists() {
+    fs::remove_dir_all(&staged)?;
+  }
+  fs::create_dir_all(staged.parent().unwrap_or(&staged))?;
+
+  let copied = aeromod_fs::copy_dir_all(src, &staged)
+    .context("Failed to copy package into the addons directory")
+    .and_then(|_| fs::rename(&staged, dst).map_err(Into::into));
+  if copied.is_err() {
+    let _ = fs::remove_dir_all(&staged);
+  }
+
+  copied
+}
+
+/// Returns where the link at `path` points to, resolving relative targets
+/// against the link's directory. `None` if `path` is not a link.
+pub(crate) fn link_target(path: &Path) -> Option<PathBuf> {
+  let target = fs::read_link(path).ok()?;
+  if target.is_relative() {
+    Some(path.parent()?.join(target))
+  } else {
+    Some(target)
+  }
+}
+
+/// Canonicalizes the longest existing ancestor of `path` and appends the
+/// rest, so that paths that do not exist (yet or anymore) still compare
+/// equal to their existing spellings.
+pub(crate) fn canonical_path(path: &Path) -> PathBuf {
+  for ancestor in path.ancestors() {
+    if let Ok(canonical) = ancestor.canonicalize() {
+      return match path.strip_prefix(ancestor) {
+        Ok(rest) if !rest.as_os_str().is_empty() => canonical.join(rest),
+        _ => canonical,
+      };
+    }
+  }
+
+  path.to_path_buf()
+}
+
+pub(crate) fn same_path(a: &Path, b: &Path) -> bool {
+  canonical_path(a) == canonical_path(b)
+}
+
+/// Whether the community folder holds a link named `id` that points to the
+/// addon `id` in the addons directory.
+pub(crate) fn is_linked(settings: &AppSettings, id: &str) -> bool {
+  link_target(&Path::new(&settings.community_dir).join(id))
+    .is_some_and(|target| same_path(&target, &Path::new(&settings.addons_dir).join(id)))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkProblem {
+  /// The link target does not exist.
+  Dangling,
+  /// The link points outside of the addons directory.
+  Foreign,
+  /// The link points to an addon that is already linked under its own name.
+  Duplicate,
+  /// The link points to an addon under a different name.
+  Misnamed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkAction {
+  /// The addon of the same name was activated again in its place.
+  Relinked,
+  Removed,
+  /// The link was left alone because it does not belong to AeroMod.
+  Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkRepair {
+  pub name: String,
+  pub target: PathBuf,
+  pub problem: LinkProblem,
+  pub action: LinkAction,
+}
+
+/// Finds links in the community folder that are dangling, point outside of
+/// the addons directory or duplicate another link, and repairs or removes
+/// them. Links to foreign locations are only replaced when an addon with the
+/// same name exists, and dangling ones are only removed when they point into
+/// the addons directory. With `dry_run` set, nothing is changed and the returned
+/// actions describe what would be done.
+pub fn reconcile_links(settings: &AppSettings, dry_run: bool) -> Result<Vec<LinkRepair>> {
+  let addons_dir = Path::new(&settings.addons_dir);
+  let canonical_addons_dir = canonical_path(addons_dir);
+
+  let mut links = Vec::new();
+  for entry in fs::read_dir(&settings.community_dir)? {
+    let path = entry?.path();
+    if let Some(target) = link_target(&path) {
+      let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+      links.push((name, path, target));
+    }
+  }
+  links.sort_by(|a, b| a.0.cmp(&b.0));
+
+  // The addon a link points to, if it points into the addons directory.
+  let owner = |target: &Path| -> Option<String> {
+    let target = target.canonicalize().ok()?;
+    let relative = target.strip_prefix(&canonical_addons_dir).ok()?;
+    let mut components = relative.components();
+    let id = components
+      .next()?
+      .as_os_str()
+      .to_string_lossy()
+      .into_owned();
+    components.next().is_none().then_some(id)
+  };
+
+  let mut linked: HashSet<String> = links
+    .iter()
+    .filter(|(name, _, target)| owner(target).as_ref() == Some(name))
+    .map(|(name, _, _)| name.clone())
+    .collect();
+
+  let mut repairs = Vec::new();
+  for (name, path, target) in links {
+    let addon_path = addons_dir.join(&name);
+
+    let (problem, action) = if !target.exists() {
+      // Links into other directories may belong to other tools.
+      let action = if addon_path.is_dir() {
+        LinkAction::Relinked
+      } else if canonical_path(&target).starts_with(&canonical_addons_dir) {
+        LinkAction::Removed
+      } else {
+        LinkAction::Skipped
+      };
+      (LinkProblem::Dangling, action)
+    } else {
+      match owner(&target) {
+        Some(id) if id == name => continue,
+        Some(id) => {
+          let free = !linked.contains(&id)
+            && fs::symlink_metadata(Path::new(&settings.community_dir).join(&id)).is_err();
+          if free {
+            linked.insert(id.clone());
+            if !dry_run {
+              aeromod_fs::remove_symlink_dir(&path)?;
+              activation::activate(settings, &id)?;
+            }
+            repairs.push(LinkRepair {
+              name,
+              target,
+              problem: LinkProblem::Misnamed,
+              action: LinkAction::Relinked,
+            });
+            continue;
+          }
+          (LinkProblem::Duplicate, LinkAction::Removed)
+        }
+        None if addon_path.is_dir() => (LinkProblem::Foreign, LinkAction::Relinked),
+        None => (LinkProblem::Foreign, LinkAction::Skipped),
+      }
+    };
+
+    if !dry_run {
+      match action {
+        LinkAction::Relinked => {
+          aeromod_fs::remove_symlink_dir(&path)?;
+          activation::activate(settings, &name)?;
+        }
+        LinkAction::Removed => aeromod_fs::remove_symlink_dir(&path)?,
+        LinkAction::Skipped => {}
+      }
+    }
+
+    repairs.push(LinkRepair {
+      name,
+      target,
+      problem,
+      action,
+    });
+  }
+
+  Ok(repairs)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::addon;
+  use crate::testing::write_package;
+  use aeromod_settings::ActivationMode;
+
+  #[test]
+  fn test_import_unmanaged_package() {
+    let (_dir, settings) = crate::testing::settings();
+
+    let package = settings.community_dir.join("airport-ltfm");
+    write_package(&package, "Airport");
+    fs::write(package.join("scenery.bgl"), "bgl").unwrap();
+    fs::create_dir_all(settings.community_dir.join("not-a-package")).unwrap();
+    // Left behind by an interrupted import.
+    let leftover = settings
+      .community_dir
+      .join(temp_name("airport-eddf", "import"));
+    fs::create_dir_all(&leftover).unwrap();
+    fs::copy(
+      package.join("manifest.json"),
+      leftover.join("manifest.json"),
+    )
+    .unwrap();
+
+    let unmanaged = find_unmanaged(&settings).unwrap();
+    assert_eq!(unmanaged.len(), 1);
+    assert_eq!(unmanaged[0].id, "airport-ltfm");
+    assert!(!unmanaged[0].conflict);
+
+    import_unmanaged(&settings, "airport-ltfm").unwrap();
+
+    assert!(package.is_symlink());
+    assert!(package.join("scenery.bgl").exists());
+    assert!(find_unmanaged(&settings).unwrap().is_empty());
+    assert!(
+      addon::parse_addon(&settings, "airport-ltfm")
+        .unwrap()
+        .enabled
+    );
+    assert!(import_unmanaged(&settings, "airport-ltfm").is_err());
+
+    // Other activation modes are kept as well.
+    let mut settings = settings;
+    settings.activation_mode = ActivationMode::Copy;
+    let package = settings.community_dir.join("airport-eddf");
+    fs::rename(&leftover, &package).unwrap();
+    import_unmanaged(&settings, "airport-eddf").unwrap();
+    assert!(activation::is_managed_copy(&package));
+    assert_eq!(
+      activation::active_mode(&settings, "airport-eddf"),
+      Some(ActivationMode::Copy)
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_reconcile_links() {
+    use std::os::unix::fs::symlink;
+
+    let (dir, settings) = crate::testing::settings();
+    let old_addons_dir = dir.path().join("old-addons");
+    let foreign_dir = dir.path().join("foreign");
+    for path in [
+      settings.addons_dir.join("airport-ltfm"),
+      settings.addons_dir.join("airport-eddf"),
+      foreign_dir.join("aircraft-a320"),
+      settings.community_dir.clone(),
+    ] {
+      fs::create_dir_all(path).unwrap();
+    }
+
+    let community = &settings.community_dir;
+    // Left behind after the addons directory moved.
+    symlink(
+      old_addons_dir.join("airport-ltfm"),
+      community.join("airport-ltfm"),
+    )
+    .unwrap();
+    // The addon was deleted.
+    symlink(
+      settings.addons_dir.join("airport-egll"),
+      community.join("airport-egll"),
+    )
+    .unwrap();
+    // Dangling, but not into the addons directory.
+    symlink(
+      old_addons_dir.join("airport-kjfk"),
+      community.join("airport-kjfk"),
+    )
+    .unwrap();
+    // Managed by another tool.
+    symlink(
+      foreign_dir.join("aircraft-a320"),
+      community.join("aircraft-a320"),
+    )
+    .unwrap();
+    symlink(
+      settings.addons_dir.join("airport-eddf"),
+      community.join("airport-eddf"),
+    )
+    .unwrap();
+    symlink(
+      settings.addons_dir.join("airport-eddf"),
+      community.join("eddf-copy"),
+    )
+    .unwrap();
+
+    assert!(!is_linked(&settings, "airport-ltfm"));
+    addon::disable_addon(&settings, "airport-ltfm").unwrap();
+    symlink(
+      old_addons_dir.join("airport-ltfm"),
+      community.join("airport-ltfm"),
+    )
+    .unwrap();
+
+    let preview = reconcile_links(&settings, true).unwrap();
+    assert_eq!(preview.len(), 5);
+    assert!(community.join("eddf-copy").is_symlink());
+
+    let repairs: Vec<_> = reconcile_links(&settings, false)
+      .unwrap()
+      .into_iter()
+      .map(|r| (r.name, r.problem, r.action))
+      .collect();
+    assert_eq!(
+      repairs,
+      [
+        (
+          "aircraft-a320".to_string(),
+          LinkProblem::Foreign,
+          LinkAction::Skipped
+        ),
+        (
+          "airport-egll".to_string(),
+          LinkProblem::Dangling,
+          LinkAction::Removed
+        ),
+        (
+          "airport-kjfk".to_string(),
+          LinkProblem::Dangling,
+          LinkAction::Skipped
+        ),
+        (
+          "airport-ltfm".to_string(),
+          LinkProblem::Dangling,
+          LinkAction::Relinked
+        ),
+        (
+          "eddf-copy".to_string(),
+          LinkProblem::Duplicate,
+          LinkAction::Removed
+        ),
+      ]
+    );
+
+    assert!(is_linked(&settings, "airport-ltfm"));
+    assert!(is_linked(&settings, "airport-eddf"));
+    assert!(!community.join("airport-egll").is_symlink());
+    assert!(community.join("airport-kjfk").is_symlink());
+    assert!(reconcile_links(&settings, false).unwrap().len() == 2);
+
+    // Relinking uses the activation mode of the library.
+    let settings = AppSettings {
+      activation_mode: ActivationMode::Copy,
+      ..settings.clone()
+    };
+    aeromod_fs::remove_symlink_dir(community.join("airport-ltfm")).unwrap();
+    symlink(
+      old_addons_dir.join("airport-ltfm"),
+      community.join("airport-ltfm"),
+    )
+    .unwrap();
+    reconcile_links(&settings, false).unwrap();
+    assert!(activation::is_managed_copy(&community.join("airport-ltfm")));
+  }
+}
@@ -0,0 +1,315 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow, bail};
+use roxmltree::Document;
+use serde::{Deserialize, Serialize};
+
+use crate::manifest::Manifest;
+use crate::sim::{self, SimManager};
+use crate::version::Version;
+
+/// A `<Package>` entry of `Content.xml`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentEntry {
+  pub name: String,
+  pub active: bool,
+}
+
+/// The sim's `Content.xml`, which lists official and marketplace packages the
+/// user deactivated in the content manager. Packages without an entry are
+/// active. The sim rewrites the file on exit, so it must not be running while
+/// the file is changed.
+///
+/// Attributes, elements and comments AeroMod does not know about are written
+/// back as they were read, in their original order.
+pub struct ContentFile {
+  path: PathBuf,
+  entries: Vec<ContentEntry>,
+  /// Attributes of `<Package>` elements besides `name` and `active`, by
+  /// lowercased package name.
+  extra_attributes: HashMap<String, Vec<(String, String)>>,
+  /// Other elements and comments inside `<Content>`, as read, with the
+  /// number of entries preceding them.
+  other_nodes: Vec<(usize, String)>,
+}
+
+impl ContentFile {
+  /// Reads `Content.xml` at `path`. A missing file has no entries.
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    let mut content = Self {
+      path,
+      entries: Vec::new(),
+      extra_attributes: HashMap::new(),
+      other_nodes: Vec::new(),
+    };
+    if !content.path.exists() {
+      return Ok(content);
+    }
+
+    let text = fs::read_to_string(&content.path).context("Failed to read Content.xml")?;
+    let document = Document::parse(&text).context("Failed to parse Content.xml")?;
+
+    for node in document
+      .root_element()
+      .children()
+      .filter(|n| n.is_element() || n.is_comment())
+    {
+      let name = node
+        .attribute("name")
+        .filter(|_| node.has_tag_name("Package"));
+      let Some(name) = name else {
+        let position = content.entries.len();
+        content
+          .other_nodes
+          .push((position, text[node.range()].to_string()));
+        continue;
+      };
+
+      content.entries.push(ContentEntry {
+        name: name.to_string(),
+        active: !node
+          .attribute("active")
+          .is_some_and(|a| a.eq_ignore_ascii_case("false")),
+      });
+
+      let extra: Vec<_> = node
+        .attributes()
+        .filter(|a| a.name() != "name" && a.name() != "active")
+        .map(|a| (a.name().to_string(), a.value().to_string()))
+        .collect();
+      if !extra.is_empty() {
+        content.extra_attributes.insert(name.to_lowercase(), extra);
+      }
+    }
+
+    Ok(content)
+  }
+
+  pub fn entries(&self) -> &[ContentEntry] {
+    &self.entries
+  }
+
+  pub fn is_active(&self, name: &str) -> bool {
+    self
+      .entries
+      .iter()
+      .find(|e| e.name.eq_ignore_ascii_case(name))
+      .is_none_or(|e| e.active)
+  }
+
+  pub fn set_active(&mut self, name: &str, active: bool) {
+    match self
+      .entries
+      .iter_mut()
+      .find(|e| e.name.eq_ignore_ascii_case(name))
+    {
+      Some(entry) => entry.active = active,
+      None => self.entries.push(ContentEntry {
+        name: name.to_string(),
+        active,
+      }),
+    }
+  }
+
+  pub fn save(&self) -> Result<()> {
+    aeromod_fs::write_atomic(&self.path, |writer| {
+      writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+      writeln!(writer, "<Content>")?;
+      let mut other_nodes = self.other_nodes.iter().peekable();
+      for (position, entry) in self.entries.iter().enumerate() {
+        while let Some((_, node)) = other_nodes.next_if(|(p, _)| *p == position) {
+          writeln!(writer, "    {}", node)?;
+        }
+        write!(
+          writer,
+          "    <Package name=\"{}\" active=\"{}\"",
+          escape(&entry.name),
+          entry.active
+        )?;
+        for (name, value) in self
+          .extra_attributes
+          .get(&entry.name.to_lowercase())
+          .into_iter()
+          .flatten()
+        {
+          write!(writer, " {}=\"{}\"", name, escape(value))?;
+        }
+        writeln!(writer, "/>")?;
+      }
+      for (_, node) in other_nodes {
+        writeln!(writer, "    {}", node)?;
+      }
+      writeln!(writer, "</Content>")?;
+      Ok(())
+    })?;
+
+    Ok(())
+  }
+}
+
+fn escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+/// An official or marketplace package installed by the sim.
+#[derive(Serialize, Deserialize)]
+pub struct OfficialPackage {
+  pub id: String,
+  pub name: String,
+  pub creator: String,
+  pub version: Version,
+  pub content_type: String,
+  pub active: bool,
+}
+
+pub(crate) fn content_file(settings: &AppSettings) -> Result<ContentFile> {
+  let path = SimManager::new(settings)
+    .content_xml_path()
+    .context("Failed to find sim directory")?;
+  ContentFile::load(path)
+}
+
+/// Lists the official and marketplace packages with their activation state
+/// from `Content.xml`, sorted by id.
+pub fn list_official_packages(settings: &AppSettings) -> Result<Vec<OfficialPackage>> {
+  let official_dir = SimManager::new(settings)
+    .official_packages_dir()
+    .ok_or_else(|| anyhow!("Official packages directory not found"))?;
+  let content = content_file(settings)?;
+
+  let mut packages = Vec::new();
+  for entry in fs::read_dir(official_dir)? {
+    let entry = entry?;
+    if !entry.file_type()?.is_dir() {
+      continue;
+    }
+
+    let Ok(manifest) = Manifest::parse(entry.path().join("manifest.json")) else {
+      continue;
+    };
+
+    let id = entry.file_name().to_string_lossy().into_owned();
+    packages.push(OfficialPackage {
+      active: content.is_active(&id),
+      id,
+      name: manifest.title,
+      creator: manifest.creator,
+      version: manifest.package_version,
+      content_type: manifest.content_type,
+    });
+  }
+
+  packages.sort_by(|a, b| a.id.cmp(&b.id));
+  Ok(packages)
+}
+
+/// Activates or deactivates official packages by id in `Content.xml`. The
+/// file is only written if a state changes.
+pub fn set_official_packages_active(
+  settings: &AppSettings,
+  packages: &BTreeMap<String, bool>,
+) -> Result<()> {
+  let mut content = content_file(settings)?;
+
+  let mut changed = false;
+  for (id, &active) in packages {
+    if content.is_active(id) != active {
+      content.set_active(id, active);
+      changed = true;
+    }
+  }
+
+  if changed {
+    if sim::is_sim_running() {
+      bail!("Close the sim before changing official packages, it overwrites Content.xml on exit");
+    }
+    content.save()?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_content_file_round_trip() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("Content.xml");
+    fs::write(
+      &path,
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<Content>
+    <!-- Written by the sim -->
+    <Package name="fs-base" active="true" source="base"/>
+    <Settings><Filter value="all"/></Settings>
+    <Package name="asobo-airport-ltfm" active="false"/>
+    <!-- End -->
+</Content>
+"#,
+    )
+    .unwrap();
+
+    let mut content = ContentFile::load(&path).unwrap();
+    assert!(content.is_active("fs-base"));
+    assert!(!content.is_active("asobo-airport-ltfm"));
+    assert!(content.is_active("asobo-airport-eddf"));
+
+    content.set_active("asobo-airport-ltfm", true);
+    content.set_active("asobo-airport-eddf", false);
+    content.save().unwrap();
+
+    let content = ContentFile::load(&path).unwrap();
+    assert_eq!(
+      content.entries(),
+      [
+        ContentEntry {
+          name: "fs-base".to_string(),
+          active: true
+        },
+        ContentEntry {
+          name: "asobo-airport-ltfm".to_string(),
+          active: true
+        },
+        ContentEntry {
+          name: "asobo-airport-eddf".to_string(),
+          active: false
+        },
+      ]
+    );
+    // Unknown attributes, elements and comments survive the rewrite in
+    // their original order, and new entries are appended after them.
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(
+      text,
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<Content>
+    <!-- Written by the sim -->
+    <Package name="fs-base" active="true" source="base"/>
+    <Settings><Filter value="all"/></Settings>
+    <Package name="asobo-airport-ltfm" active="true"/>
+    <!-- End -->
+    <Package name="asobo-airport-eddf" active="false"/>
+</Content>
+"#
+    );
+
+    assert!(
+      ContentFile::load(dir.path().join("missing.xml"))
+        .unwrap()
+        .entries()
+        .is_empty()
+    );
+  }
+}
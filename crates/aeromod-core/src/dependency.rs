@@ -0,0 +1,436 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use aeromod_settings::AppSettings;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::addon;
+use crate::content;
+use crate::manifest::{Dependency, Manifest};
+use crate::sim::SimManager;
+use crate::version::Version;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum DependencyStatus {
+  Missing,
+  Disabled,
+  Outdated { installed_version: Version },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyIssue {
+  pub addon_id: String,
+  pub dependency: String,
+  pub required_version: Version,
+  #[serde(flatten)]
+  pub status: DependencyStatus,
+}
+
+/// An addon that requires a newer sim than the installed official packages
+/// prove. The sim itself may still be new enough, so the addon's
+/// compatibility is unknown rather than ruled out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameVersionIssue {
+  pub addon_id: String,
+  pub minimum_game_version: Version,
+  /// The sim is at least this version, see [`PackageIndex::min_sim_version`].
+  pub min_sim_version: Version,
+}
+
+struct InstalledPackage {
+  version: Version,
+  minimum_game_version: Version,
+  enabled: bool,
+  /// Whether the package lives in the addons directory and can be toggled.
+  managed: bool,
+  dependencies: Vec<Dependency>,
+}
+
+/// Snapshot of every package the sim can load: the managed addons plus the
+/// official and marketplace packages.
+pub struct PackageIndex {
+  packages: HashMap<String, InstalledPackage>,
+}
+
+impl PackageIndex {
+  pub fn load(settings: &AppSettings) -> Result<Self> {
+    let mut packages = HashMap::new();
+
+    // An unreadable official folder leaves only the managed addons to check
+    // against, rather than failing every caller. Likewise, official packages
+    // count as active without a readable `Content.xml`.
+    let content = content::content_file(settings).ok();
+    let official_entries = SimManager::new(settings)
+      .official_packages_dir()
+      .and_then(|dir| fs::read_dir(dir).ok());
+    if let Some(entries) = official_entries {
+      for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+          continue;
+        };
+
+        if let Ok(manifest) = Manifest::parse(path.join("manifest.json")) {
+          let enabled = content.as_ref().is_none_or(|c| c.is_active(&name));
+          packages.insert(
+            name,
+            InstalledPackage {
+              version: manifest.package_version,
+              minimum_game_version: manifest.minimum_game_version,
+              enabled,
+              managed: false,
+              dependencies: manifest.dependencies,
+            },
+          );
+        }
+      }
+    }
+
+    for addon in addon::get_addons(settings)? {
+      let manifest_path = Path::new(&settings.addons_dir)
+        .join(&addon.id)
+        .join("manifest.json");
+      let (minimum_game_version, dependencies) = Manifest::parse(manifest_path)
+        .map(|m| (m.minimum_game_version, m.dependencies))
+        .unwrap_or_default();
+
+      packages.insert(
+        addon.id,
+        InstalledPackage {
+          version: addon.version,
+          minimum_game_version,
+          enabled: addon.enabled,
+          managed: true,
+          dependencies,
+        },
+      );
+    }
+
+    Ok(Self { packages })
+  }
+
+  /// Returns a lower bound of the installed sim version: the highest
+  /// `minimum_game_version` among the official packages, which the sim must
+  /// satisfy to load them. The sim may be newer. Returns `None` when no
+  /// official packages were found.
+  pub fn min_sim_version(&self) -> Option<Version> {
+    self
+      .packages
+      .values()
+      .filter(|p| !p.managed && p.minimum_game_version.is_numeric())
+      .map(|p| p.minimum_game_version.clone())
+      .max()
+  }
+
+  /// Lists the managed addons whose `minimum_game_version` exceeds the
+  /// lower bound of the sim version, as addons of unknown compatibility.
+  pub fn check_game_versions(&self) -> Vec<GameVersionIssue> {
+    let Some(min_sim_version) = self.min_sim_version() else {
+      return Vec::new();
+    };
+
+    let mut issues: Vec<_> = self
+      .packages
+      .iter()
+      .filter(|(_, p)| p.managed && p.minimum_game_version > min_sim_version)
+      .map(|(id, p)| GameVersionIssue {
+        addon_id: id.clone(),
+        minimum_game_version: p.minimum_game_version.clone(),
+        min_sim_version: min_sim_version.clone(),
+      })
+      .collect();
+    issues.sort_by(|a, b| a.addon_id.cmp(&b.addon_id));
+
+    issues
+  }
+
+  /// Checks the direct dependencies of a single addon.
+  pub fn check_addon(&self, id: &str) -> Result<Vec<DependencyIssue>> {
+    let package = self
+      .packages
+      .get(id)
+      .ok_or_else(|| anyhow!("Addon '{}' not found in addons directory", id))?;
+
+    let issues = package
+      .dependencies
+      .iter()
+      .filter_map(|dep| self.check_dependency(id, dep))
+      .collect();
+
+    Ok(issues)
+  }
+
+  /// Checks the dependencies of every enabled managed addon.
+  pub fn check_enabled(&self) -> Vec<DependencyIssue> {
+    let mut ids: Vec<_> = self
+      .packages
+      .iter()
+      .filter(|(_, p)| p.managed && p.enabled)
+      .map(|(id, _)| id.as_str())
+      .collect();
+    ids.sort();
+
+    ids
+      .into_iter()
+      .flat_map(|id| {
+        self.packages[id]
+          .dependencies
+          .iter()
+          .filter_map(move |dep| self.check_dependency(id, dep))
+      })
+      .collect()
+  }
+
+  /// Returns the managed addons that the given addons depend on, directly
+  /// or transitively.
+  pub fn managed_dependencies<'a, I>(&self, ids: I) -> BTreeSet<String>
+  where
+    I: IntoIterator<Item = &'a str>,
+  {
+    let mut visited: BTreeSet<&str> = BTreeSet::new();
+    let mut queue: VecDeque<&str> = ids.into_iter().collect();
+    let mut managed = BTreeSet::new();
+
+    while let Some(id) = queue.pop_front() {
+      let Some(package) = self.packages.get(id) else {
+        continue;
+      };
+
+      for dep in &package.dependencies {
+        let Some((name, dep_package)) = self.packages.get_key_value(dep.name.as_str()) else {
+          continue;
+        };
+
+        if !visited.insert(name) {
+          continue;
+        }
+
+        if dep_package.managed {
+          managed.insert(name.clone());
+        }
+        queue.push_back(name);
+      }
+    }
+
+    managed
+  }
+
+  /// Returns the disabled managed addons that the given addons depend on,
+  /// directly or transitively.
+  pub fn disabled_dependencies<'a, I>(&self, ids: I) -> BTreeSet<String>
+  where
+    I: IntoIterator<Item = &'a str>,
+  {
+    self
+      .managed_dependencies(ids)
+      .into_iter()
+      .filter(|id| !self.packages[id].enabled)
+      .collect()
+  }
+
+  /// Returns the version of an addon in the addons directory.
+  pub fn addon_version(&self, id: &str) -> Option<&Version> {
+    self
+      .packages
+      .get(id)
+      .filter(|p| p.managed)
+      .map(|p| &p.version)
+  }
+
+  pub fn check_dependency(&self, addon_id: &str, dep: &Dependency) -> Option<DependencyIssue> {
+    let status = match self.packages.get(&dep.name) {
+      None => DependencyStatus::Missing,
+      Some(p) if p.version < dep.package_version => DependencyStatus::Outdated {
+        installed_version: p.version.clone(),
+      },
+      Some(p) if !p.enabled => DependencyStatus::Disabled,
+      Some(_) => return None,
+    };
+
+    Some(DependencyIssue {
+      addon_id: addon_id.to_string(),
+      dependency: dep.name.clone(),
+      required_version: dep.package_version.clone(),
+      status,
+    })
+  }
+}
+
+pub fn check_addon_dependencies(settings: &AppSettings, id: &str) -> Result<Vec<DependencyIssue>> {
+  PackageIndex::load(settings)?.check_addon(id)
+}
+
+/// Enables an addon together with every disabled addon it depends on and
+/// returns the ids of the dependencies that were enabled. If any of them
+/// cannot be enabled, the ones enabled so far are disabled again.
+pub fn enable_addon_with_dependencies(settings: &AppSettings, id: &str) -> Result<Vec<String>> {
+  let index = PackageIndex::load(settings)?;
+  let dependencies = index.disabled_dependencies([id]);
+
+  let mut enabled = Vec::new();
+  let result = dependencies
+    .iter()
+    .map(String::as_str)
+    .chain([id])
+    .try_for_each(|dep| {
+      addon::enable_addon(settings, dep)?;
+      enabled.push(dep);
+      Ok::<_, anyhow::Error>(())
+    });
+
+  if let Err(e) = result {
+    for dep in enabled.into_iter().rev() {
+      let _ = addon::disable_addon(settings, dep);
+    }
+    return Err(e);
+  }
+
+  Ok(dependencies.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{write_manifest, write_versioned_package};
+  use tempfile::TempDir;
+
+  fn write_addon(settings: &AppSettings, id: &str, version: &str, deps: &[(&str, &str)]) {
+    write_versioned_package(&settings.addons_dir.join(id), id, version, deps);
+  }
+
+  fn write_official_package(dir: &Path, id: &str, minimum_game_version: &str) {
+    write_manifest(
+      &dir.join(id),
+      serde_json::json!({
+        "content_type": "CORE",
+        "title": id,
+        "package_version": "0.1.0",
+        "minimum_game_version": minimum_game_version,
+      }),
+    );
+  }
+
+  fn settings() -> (TempDir, AppSettings) {
+    let (dir, mut settings) = crate::testing::settings();
+    settings.community_dir = dir.path().join("Packages").join("Community");
+    fs::create_dir_all(&settings.community_dir).unwrap();
+    (dir, settings)
+  }
+
+  #[test]
+  fn test_check_addon_reports_missing_disabled_and_outdated() {
+    let (_dir, settings) = settings();
+    write_addon(
+      &settings,
+      "airport",
+      "1.0.0",
+      &[("library", "1.2.0"), ("textures", "1.0.0"), ("mesh", "1.0")],
+    );
+    write_addon(&settings, "library", "1.10", &[]);
+    write_addon(&settings, "textures", "0.9.5", &[]);
+
+    let index = PackageIndex::load(&settings).unwrap();
+    let statuses: Vec<_> = index
+      .check_addon("airport")
+      .unwrap()
+      .into_iter()
+      .map(|issue| (issue.dependency, issue.status))
+      .collect();
+
+    assert_eq!(
+      statuses,
+      [
+        ("library".to_string(), DependencyStatus::Disabled),
+        (
+          "textures".to_string(),
+          DependencyStatus::Outdated {
+            installed_version: Version::parse("0.9.5")
+          }
+        ),
+        ("mesh".to_string(), DependencyStatus::Missing),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_enable_addon_with_dependencies_is_transitive() {
+    let (_dir, settings) = settings();
+    write_addon(&settings, "airport", "1.0.0", &[("library", "1.0.0")]);
+    write_addon(&settings, "library", "1.0.0", &[("base", "1.0.0")]);
+    write_addon(&settings, "base", "1.0.0", &[("library", "1.0.0")]);
+
+    let enabled = enable_addon_with_dependencies(&settings, "airport").unwrap();
+    assert_eq!(enabled, ["base", "library"]);
+
+    for id in ["airport", "library", "base"] {
+      assert!(settings.community_dir.join(id).exists());
+    }
+  }
+
+  #[test]
+  fn test_enable_addon_with_dependencies_rolls_back() {
+    let (_dir, settings) = settings();
+    write_addon(&settings, "airport", "1.0.0", &[("library", "1.0.0")]);
+    write_addon(&settings, "library", "1.0.0", &[]);
+    // A package of the same name placed in the community folder by hand.
+    fs::create_dir_all(settings.community_dir.join("airport")).unwrap();
+
+    assert!(enable_addon_with_dependencies(&settings, "airport").is_err());
+    assert!(!settings.community_dir.join("library").exists());
+    assert!(settings.community_dir.join("airport").exists());
+  }
+
+  #[test]
+  fn test_check_game_versions_against_official_packages() {
+    let (dir, settings) = settings();
+    let official = dir
+      .path()
+      .join("Packages")
+      .join("Official")
+      .join("OneStore");
+    write_official_package(&official, "fs-base", "1.37.19");
+    write_official_package(&official, "asobo-airport-ltfm", "1.36.2");
+    write_official_package(&official, "asobo-airport-eddf", "1.36.2");
+    fs::write(
+      dir.path().join("Content.xml"),
+      r#"<Content><Package name="asobo-airport-eddf" active="false"/></Content>"#,
+    )
+    .unwrap();
+    write_addon(
+      &settings,
+      "airport",
+      "1.0.0",
+      &[("asobo-airport-ltfm", "0.1"), ("asobo-airport-eddf", "0.1")],
+    );
+    write_manifest(
+      &settings.addons_dir.join("aircraft"),
+      serde_json::json!({
+        "content_type": "AIRCRAFT",
+        "title": "Aircraft",
+        "package_version": "1.0.0",
+        "minimum_game_version": "1.38.0",
+      }),
+    );
+
+    let index = PackageIndex::load(&settings).unwrap();
+    assert_eq!(index.min_sim_version(), Some(Version::parse("1.37.19")));
+    let statuses: Vec<_> = index
+      .check_addon("airport")
+      .unwrap()
+      .into_iter()
+      .map(|issue| (issue.dependency, issue.status))
+      .collect();
+    assert_eq!(
+      statuses,
+      [("asobo-airport-eddf".to_string(), DependencyStatus::Disabled)]
+    );
+
+    let issues = index.check_game_versions();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].addon_id, "aircraft");
+    assert_eq!(issues[0].minimum_game_version, Version::parse("1.38"));
+  }
+}
@@ -0,0 +1,222 @@
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::scenery::{AirportScenery, SceneryCache};
+
+const CSV_HEADER: &str = "addon_id,icao,latitude,longitude,altitude,runway_count";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+  GeoJson,
+  Kml,
+  Csv,
+}
+
+impl ExportFormat {
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+    let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+      "geojson" | "json" => Some(ExportFormat::GeoJson),
+      "kml" => Some(ExportFormat::Kml),
+      "csv" => Some(ExportFormat::Csv),
+      _ => None,
+    }
+  }
+}
+
+/// Writes the community airports of the scenery cache to `path`, picking the
+/// format from the file extension.
+pub fn export_scenery_cache<P: AsRef<Path>>(cache: &SceneryCache, path: P) -> Result<()> {
+  let path = path.as_ref();
+  let format = ExportFormat::from_path(path)
+    .ok_or_else(|| anyhow!("Unsupported export format: '{}'", path.display()))?;
+
+  aeromod_fs::write_atomic(path, |writer| {
+    write_airports(cache.all_airports(), format, writer)
+  })?;
+
+  Ok(())
+}
+
+pub fn write_airports<W: Write>(
+  airports: &[AirportScenery],
+  format: ExportFormat,
+  writer: &mut W,
+) -> io::Result<()> {
+  match format {
+    ExportFormat::GeoJson => write_geojson(airports, writer),
+    ExportFormat::Kml => write_kml(airports, writer),
+    ExportFormat::Csv => write_csv(airports, writer),
+  }
+}
+
+fn write_geojson<W: Write>(airports: &[AirportScenery], writer: &mut W) -> io::Result<()> {
+  let features: Vec<_> = airports
+    .iter()
+    .map(|a| {
+      json!({
+        "type": "Feature",
+        "geometry": {
+          "type": "Point",
+          "coordinates": [a.longitude, a.latitude, a.altitude],
+        },
+        "properties": {
+          "addon_id": a.addon_id,
+          "icao": a.icao,
+          "altitude": a.altitude,
+          "runway_count": a.runway_count,
+        },
+      })
+    })
+    .collect();
+
+  let collection = json!({
+    "type": "FeatureCollection",
+    "features": features,
+  });
+
+  serde_json::to_writer_pretty(&mut *writer, &collection)?;
+  writeln!(writer)
+}
+
+fn write_kml<W: Write>(airports: &[AirportScenery], writer: &mut W) -> io::Result<()> {
+  writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+  writeln!(writer, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+  writeln!(writer, "<Document>")?;
+  writeln!(writer, "<name>AeroMod Scenery</name>")?;
+
+  for a in airports {
+    writeln!(writer, "<Placemark>")?;
+    writeln!(writer, "<name>{}</name>", escape_xml(&a.icao))?;
+    writeln!(
+      writer,
+      "<description>{}</description>",
+      escape_xml(&a.addon_id)
+    )?;
+    writeln!(writer, "<ExtendedData>")?;
+    write_kml_data(writer, "addon_id", &a.addon_id)?;
+    write_kml_data(writer, "icao", &a.icao)?;
+    write_kml_data(writer, "altitude", &a.altitude.to_string())?;
+    write_kml_data(writer, "runway_count", &a.runway_count.to_string())?;
+    writeln!(writer, "</ExtendedData>")?;
+    writeln!(
+      writer,
+      "<Point><altitudeMode>absolute</altitudeMode><coordinates>{},{},{}</coordinates></Point>",
+      a.longitude, a.latitude, a.altitude
+    )?;
+    writeln!(writer, "</Placemark>")?;
+  }
+
+  writeln!(writer, "</Document>")?;
+  writeln!(writer, "</kml>")
+}
+
+fn write_kml_data<W: Write>(writer: &mut W, name: &str, value: &str) -> io::Result<()> {
+  writeln!(
+    writer,
+    r#"<Data name="{}"><value>{}</value></Data>"#,
+    name,
+    escape_xml(value)
+  )
+}
+
+fn write_csv<W: Write>(airports: &[AirportScenery], writer: &mut W) -> io::Result<()> {
+  writeln!(writer, "{}", CSV_HEADER)?;
+
+  for a in airports {
+    writeln!(
+      writer,
+      "{},{},{},{},{},{}",
+      escape_csv(&a.addon_id),
+      escape_csv(&a.icao),
+      a.latitude,
+      a.longitude,
+      a.altitude,
+      a.runway_count
+    )?;
+  }
+
+  Ok(())
+}
+
+fn escape_xml(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+fn escape_csv(value: &str) -> String {
+  if value.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn airports() -> Vec<AirportScenery> {
+    vec![AirportScenery {
+      addon_id: "fly, \"tampa\" & co".to_string(),
+      bgl_path: "scenery/ktpa.bgl".to_string(),
+      icao: "KTPA".to_string(),
+      latitude: 27.97,
+      longitude: -82.53,
+      altitude: 8.0,
+      runway_count: 3,
+    }]
+  }
+
+  fn export(format: ExportFormat) -> String {
+    let mut buf = Vec::new();
+    write_airports(&airports(), format, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+  }
+
+  #[test]
+  fn test_export_format_from_path() {
+    assert_eq!(
+      ExportFormat::from_path("map.GeoJSON"),
+      Some(ExportFormat::GeoJson)
+    );
+    assert_eq!(ExportFormat::from_path("map.kml"), Some(ExportFormat::Kml));
+    assert_eq!(ExportFormat::from_path("map.csv"), Some(ExportFormat::Csv));
+    assert_eq!(ExportFormat::from_path("map.txt"), None);
+  }
+
+  #[test]
+  fn test_write_geojson() {
+    let value: serde_json::Value = serde_json::from_str(&export(ExportFormat::GeoJson)).unwrap();
+    let feature = &value["features"][0];
+    assert_eq!(feature["geometry"]["coordinates"][0], -82.53);
+    assert_eq!(feature["geometry"]["coordinates"][1], 27.97);
+    assert_eq!(feature["properties"]["icao"], "KTPA");
+    assert_eq!(feature["properties"]["runway_count"], 3);
+  }
+
+  #[test]
+  fn test_write_kml_and_csv_escape_values() {
+    let kml = export(ExportFormat::Kml);
+    assert!(kml.contains("<description>fly, &quot;tampa&quot; &amp; co</description>"));
+    assert!(kml.contains("<coordinates>-82.53,27.97,8</coordinates>"));
+
+    let csv = export(ExportFormat::Csv);
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines[0], CSV_HEADER);
+    assert_eq!(
+      lines[1],
+      "\"fly, \"\"tampa\"\" & co\",KTPA,27.97,-82.53,8,3"
+    );
+  }
+}
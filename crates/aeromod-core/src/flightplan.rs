@@ -0,0 +1,343 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail};
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+
+use crate::preset::{self, Preset};
+use crate::scenery::{AirportScenery, SceneryCache};
+
+const EARTH_RADIUS_NM: f64 = 3440.065;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+  pub ident: String,
+  pub latitude: f64,
+  pub longitude: f64,
+  pub is_airport: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FlightPlan {
+  pub departure: Option<String>,
+  pub destination: Option<String>,
+  pub alternates: Vec<String>,
+  pub waypoints: Vec<Waypoint>,
+}
+
+impl FlightPlan {
+  /// Reads an MSFS `.pln` flight plan.
+  pub fn parse_pln<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let data = fs::read_to_string(path)?;
+    Self::from_pln_str(&data)
+  }
+
+  pub fn from_pln_str(data: &str) -> Result<Self> {
+    let doc = Document::parse(data.trim_start_matches('\u{feff}'))?;
+    let plan = doc
+      .descendants()
+      .find(|n| n.has_tag_name("FlightPlan.FlightPlan"))
+      .context("No FlightPlan.FlightPlan element found")?;
+
+    let mut waypoints = Vec::new();
+    for wp in plan.children().filter(|n| n.has_tag_name("ATCWaypoint")) {
+      let ident = child_text(wp, "ICAOIdent")
+        .or_else(|| wp.attribute("id"))
+        .unwrap_or_default()
+        .to_string();
+      let position = child_text(wp, "WorldPosition")
+        .with_context(|| format!("Waypoint '{}' has no position", ident))?;
+      let (latitude, longitude) = parse_lla(position)?;
+      let is_airport = child_text(wp, "ATCWaypointType") == Some("Airport");
+
+      waypoints.push(Waypoint {
+        ident,
+        latitude,
+        longitude,
+        is_airport,
+      });
+    }
+
+    Ok(Self {
+      departure: child_text(plan, "DepartureID").map(str::to_uppercase),
+      destination: child_text(plan, "DestinationID").map(str::to_uppercase),
+      alternates: Vec::new(),
+      waypoints,
+    })
+  }
+
+  /// Builds a plan from plain ICAO codes. The route runs directly from the
+  /// departure to the destination.
+  pub fn from_airports(
+    departure: Option<&str>,
+    destination: Option<&str>,
+    alternates: &[String],
+  ) -> Self {
+    let normalize = |icao: &str| icao.trim().to_uppercase();
+
+    Self {
+      departure: departure.map(normalize),
+      destination: destination.map(normalize),
+      alternates: alternates.iter().map(|a| normalize(a)).collect(),
+      waypoints: Vec::new(),
+    }
+  }
+
+  /// Returns every airport the plan lands at or departs from, including
+  /// alternates and airport waypoints.
+  pub fn airports(&self) -> BTreeSet<String> {
+    self
+      .departure
+      .iter()
+      .chain(&self.destination)
+      .chain(&self.alternates)
+      .cloned()
+      .chain(
+        self
+          .waypoints
+          .iter()
+          .filter(|wp| wp.is_airport)
+          .map(|wp| wp.ident.to_uppercase()),
+      )
+      .filter(|icao| !icao.is_empty())
+      .collect()
+  }
+
+  /// Returns the route as a list of positions. Plans without waypoints fall
+  /// back to the departure and destination positions found in the cache.
+  fn route(&self, cache: &SceneryCache) -> Vec<(f64, f64)> {
+    if !self.waypoints.is_empty() {
+      return self
+        .waypoints
+        .iter()
+        .map(|wp| (wp.latitude, wp.longitude))
+        .collect();
+    }
+
+    let find = |icao: &String| {
+      cache
+        .all_airports()
+        .iter()
+        .chain(cache.official_airports())
+        .find(|a| &a.icao == icao)
+        .map(|a| (a.latitude, a.longitude))
+    };
+
+    self
+      .departure
+      .iter()
+      .chain(&self.destination)
+      .filter_map(find)
+      .collect()
+  }
+
+  /// Finds the scenery addons that provide one of the plan's airports or an
+  /// airport within `corridor_nm` nautical miles of the route.
+  pub fn scenery_addons(&self, cache: &SceneryCache, corridor_nm: f64) -> BTreeSet<String> {
+    let airports = self.airports();
+    let route = self.route(cache);
+
+    cache
+      .all_airports()
+      .iter()
+      .filter(|a| airports.contains(&a.icao) || is_near_route(a, &route, corridor_nm))
+      .map(|a| a.addon_id.clone())
+      .collect()
+  }
+
+  /// Builds a preset enabling the scenery along this plan together with the
+  /// given base addons.
+  pub fn to_preset(
+    &self,
+    cache: &SceneryCache,
+    name: &str,
+    corridor_nm: f64,
+    base_addons: &[String],
+  ) -> Preset {
+    let mut addons = self.scenery_addons(cache, corridor_nm);
+    addons.extend(base_addons.iter().cloned());
+
+    let airports: Vec<_> = self.airports().into_iter().collect();
+
+    Preset {
+      id: preset::slugify(name),
+      name: name.to_string(),
+      description: Some(format!("Scenery for {}", airports.join(", "))),
+      addons: addons.into_iter().collect(),
+      tags: Vec::new(),
+      official_packages: Default::default(),
+    }
+  }
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+  node
+    .descendants()
+    .find(|n| n.has_tag_name(tag))
+    .and_then(|n| n.text())
+    .map(str::trim)
+}
+
+/// Parses a `.pln` position such as `N27° 58' 31.20",W82° 32' 0.00",+000026.00`
+/// into decimal latitude and longitude.
+fn parse_lla(value: &str) -> Result<(f64, f64)> {
+  let mut parts = value.split(',');
+  let latitude = parts.next().map(parse_dms).transpose()?;
+  let longitude = parts.next().map(parse_dms).transpose()?;
+
+  match (latitude, longitude) {
+    (Some(lat), Some(lon)) => Ok((lat, lon)),
+    _ => Err(anyhow!("Invalid position '{}'", value)),
+  }
+}
+
+fn parse_dms(value: &str) -> Result<f64> {
+  let value = value.trim();
+  let mut chars = value.chars();
+  let sign = match chars.next() {
+    Some('N' | 'E') => 1.0,
+    Some('S' | 'W') => -1.0,
+    _ => bail!("Invalid coordinate '{}'", value),
+  };
+
+  let mut degrees = 0.0;
+  for (component, divisor) in chars
+    .as_str()
+    .split(['°', '\'', '"'])
+    .map(str::trim)
+    .filter(|c| !c.is_empty())
+    .zip([1.0, 60.0, 3600.0])
+  {
+    let component: f64 = component
+      .parse()
+      .with_context(|| format!("Invalid coordinate '{}'", value))?;
+    degrees += component / divisor;
+  }
+
+  Ok(sign * degrees)
+}
+
+fn is_near_route(airport: &AirportScenery, route: &[(f64, f64)], corridor_nm: f64) -> bool {
+  if corridor_nm <= 0.0 || route.is_empty() {
+    return false;
+  }
+
+  let point = (airport.latitude, airport.longitude);
+  if route.len() == 1 {
+    return distance_nm(route[0], point) <= corridor_nm;
+  }
+
+  route
+    .windows(2)
+    .any(|leg| distance_to_leg_nm(leg[0], leg[1], point) <= corridor_nm)
+}
+
+/// Great-circle distance between two `(latitude, longitude)` positions.
+fn distance_nm(a: (f64, f64), b: (f64, f64)) -> f64 {
+  angular_distance(a, b) * EARTH_RADIUS_NM
+}
+
+fn angular_distance((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+  let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+  let dlat = lat2 - lat1;
+  let dlon = (lon2 - lon1).to_radians();
+
+  let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+  2.0 * h.sqrt().min(1.0).asin()
+}
+
+fn initial_bearing((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+  let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+  let dlon = (lon2 - lon1).to_radians();
+
+  let y = dlon.sin() * lat2.cos();
+  let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+  y.atan2(x)
+}
+
+/// Distance from `point` to the great-circle leg between `start` and `end`,
+/// falling back to the nearest endpoint when the point lies beyond the leg.
+fn distance_to_leg_nm(start: (f64, f64), end: (f64, f64), point: (f64, f64)) -> f64 {
+  let leg = angular_distance(start, end);
+  let to_point = angular_distance(start, point);
+  let relative_bearing = initial_bearing(start, point) - initial_bearing(start, end);
+
+  let cross_track = (to_point.sin() * relative_bearing.sin()).asin();
+  let along_track = (to_point.cos() / cross_track.cos()).clamp(-1.0, 1.0).acos();
+
+  if relative_bearing.cos() < 0.0 || along_track > leg {
+    distance_nm(start, point).min(distance_nm(end, point))
+  } else {
+    cross_track.abs() * EARTH_RADIUS_NM
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PLN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<SimBase.Document Type="AceXML" version="1,1">
+  <FlightPlan.FlightPlan>
+    <DepartureID>KTPA</DepartureID>
+    <DestinationID>KMIA</DestinationID>
+    <ATCWaypoint id="KTPA">
+      <ATCWaypointType>Airport</ATCWaypointType>
+      <WorldPosition>N27° 58' 31.20",W82° 32' 0.00",+000026.00</WorldPosition>
+      <ICAO><ICAOIdent>KTPA</ICAOIdent></ICAO>
+    </ATCWaypoint>
+    <ATCWaypoint id="KMIA">
+      <ATCWaypointType>Airport</ATCWaypointType>
+      <WorldPosition>N25° 47' 36.00",W80° 17' 24.00",+000008.00</WorldPosition>
+      <ICAO><ICAOIdent>KMIA</ICAOIdent></ICAO>
+    </ATCWaypoint>
+  </FlightPlan.FlightPlan>
+</SimBase.Document>"#;
+
+  fn airport(addon_id: &str, icao: &str, latitude: f64, longitude: f64) -> AirportScenery {
+    AirportScenery {
+      addon_id: addon_id.to_string(),
+      bgl_path: String::new(),
+      icao: icao.to_string(),
+      latitude,
+      longitude,
+      altitude: 0.0,
+      runway_count: 1,
+    }
+  }
+
+  #[test]
+  fn test_parse_pln() {
+    let plan = FlightPlan::from_pln_str(PLN).unwrap();
+    assert_eq!(plan.departure.as_deref(), Some("KTPA"));
+    assert_eq!(plan.destination.as_deref(), Some("KMIA"));
+    assert_eq!(plan.waypoints.len(), 2);
+    assert!((plan.waypoints[0].latitude - 27.9753).abs() < 1e-4);
+    assert!((plan.waypoints[0].longitude + 82.5333).abs() < 1e-4);
+  }
+
+  #[test]
+  fn test_scenery_addons_along_route() {
+    let cache = SceneryCache::from_airports(vec![
+      airport("tampa", "KTPA", 27.9753, -82.5333),
+      airport("miami", "KMIA", 25.7933, -80.29),
+      // Fort Myers lies roughly 28 nm off the direct route.
+      airport("fort-myers", "KRSW", 26.5362, -81.7552),
+      airport("atlanta", "KATL", 33.6367, -84.4281),
+    ]);
+
+    let plan = FlightPlan::from_pln_str(PLN).unwrap();
+    let addons: Vec<_> = plan.scenery_addons(&cache, 0.0).into_iter().collect();
+    assert_eq!(addons, ["miami", "tampa"]);
+
+    let addons: Vec<_> = plan.scenery_addons(&cache, 30.0).into_iter().collect();
+    assert_eq!(addons, ["fort-myers", "miami", "tampa"]);
+
+    let plan = FlightPlan::from_airports(Some("ktpa"), Some("kmia"), &["KRSW".to_string()]);
+    let preset = plan.to_preset(&cache, "Tampa to Miami", 0.0, &["base-mesh".to_string()]);
+    assert_eq!(preset.id, "tampa-to-miami");
+    assert_eq!(preset.addons, ["base-mesh", "fort-myers", "miami", "tampa"]);
+  }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::activation;
+use crate::baseline;
+
+/// Files at the package root that describe the package and are therefore not
+/// part of its layout.
+pub(crate) const EXCLUDED_FILES: &[&str] = &["layout.json", "manifest.json"];
+
+/// Offset between the Windows FILETIME epoch (1601-01-01) and the Unix epoch
+/// in 100 ns intervals.
+const FILETIME_UNIX_OFFSET: u64 = 116_444_736_000_000_000;
+
+#[derive(Serialize, Deserialize)]
+pub struct Layout {
+  pub content: Vec<LayoutContent>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LayoutContent {
+  pub path: String,
+  pub size: u64,
+  /// Last modification time as a Windows FILETIME.
+  pub date: u64,
+}
+
+impl Layout {
+  /// Lists every file of the package in `path`, sorted by path.
+  pub fn generate<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let path = path.as_ref();
+    let mut content = Vec::new();
+
+    for entry in WalkDir::new(path).min_depth(1).sort_by_file_name() {
+      let entry = entry?;
+      if !entry.file_type().is_file() {
+        continue;
+      }
+
+      let relative = entry.path().strip_prefix(path)?;
+      if relative
+        .to_str()
+        .is_some_and(|name| EXCLUDED_FILES.contains(&name))
+      {
+        continue;
+      }
+
+      let metadata = entry.metadata()?;
+      content.push(LayoutContent {
+        path: baseline::relative_path(path, entry.path())?,
+        size: metadata.len(),
+        date: file_time(&metadata),
+      });
+    }
+
+    content.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(Self { content })
+  }
+
+  pub fn total_size(&self) -> u64 {
+    self.content.iter().map(|c| c.size).sum()
+  }
+}
+
+/// Returns the modification time of a file as a Windows FILETIME, the format
+/// used for dates in `layout.json`.
+pub(crate) fn file_time(metadata: &Metadata) -> u64 {
+  metadata
+    .modified()
+    .ok()
+    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+    .map(|d| d.as_nanos() as u64 / 100 + FILETIME_UNIX_OFFSET)
+    .unwrap_or(FILETIME_UNIX_OFFSET)
+}
+
+/// Rewrites `layout.json` from the contents of the package in `path` and
+/// updates `total_package_size` in its manifest. Other manifest fields are
+/// kept as they are.
+pub fn rebuild_layout<P: AsRef<Path>>(path: P) -> Result<Layout> {
+  let path = path.as_ref();
+  let manifest_path = path.join("manifest.json");
+  let data = fs::read_to_string(&manifest_path).context("Failed to read manifest.json")?;
+  let mut manifest: serde_json::Value = serde_json::from_str(data.trim_start_matches('\u{feff}'))
+    .context("Failed to parse manifest.json")?;
+  let fields = manifest
+    .as_object_mut()
+    .ok_or_else(|| anyhow!("manifest.json is not a JSON object"))?;
+
+  let layout = Layout::generate(path)?;
+  fields.insert(
+    "total_package_size".to_string(),
+    serde_json::Value::String(format!("{:020}", layout.total_size())),
+  );
+
+  aeromod_fs::write_atomic(path.join("layout.json"), |writer| {
+    serde_json::to_writer_pretty(writer, &layout).map_err(Into::into)
+  })?;
+  aeromod_fs::write_atomic(&manifest_path, |writer| {
+    serde_json::to_writer_pretty(writer, &manifest).map_err(Into::into)
+  })?;
+
+  Ok(layout)
+}
+
+/// Rebuilds the layout of an installed addon and refreshes its hard-linked or
+/// copied files in the community folder.
+pub fn rebuild_addon_layout(settings: &AppSettings, id: &str) -> Result<Layout> {
+  let addon_path = Path::new(&settings.addons_dir).join(id);
+  if !addon_path.exists() {
+    return Err(anyhow!("Addon '{}' not found in addons directory", id));
+  }
+
+  let layout = rebuild_layout(addon_path)?;
+  activation::refresh(settings, id)?;
+
+  Ok(layout)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_rebuild_layout_lists_files_and_updates_manifest() {
+    let dir = tempdir().unwrap();
+    let package = dir.path().join("airport-ltfm");
+    fs::create_dir_all(package.join("scenery").join("global")).unwrap();
+    fs::write(
+      package.join("manifest.json"),
+      "{\"title\": \"Airport\", \"content_type\": \"SCENERY\", \"total_package_size\": \"0\"}",
+    )
+    .unwrap();
+    fs::write(package.join("layout.json"), "{\"content\": []}").unwrap();
+    fs::write(
+      package.join("scenery").join("global").join("a.bgl"),
+      "12345",
+    )
+    .unwrap();
+    fs::write(package.join("ContentInfo.png"), "png").unwrap();
+
+    let layout = rebuild_layout(&package).unwrap();
+    let paths: Vec<_> = layout.content.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(paths, ["ContentInfo.png", "scenery/global/a.bgl"]);
+    assert!(layout.content[0].date > FILETIME_UNIX_OFFSET);
+
+    let written: Layout =
+      serde_json::from_str(&fs::read_to_string(package.join("layout.json")).unwrap()).unwrap();
+    assert_eq!(written.content.len(), 2);
+
+    let manifest: serde_json::Value =
+      serde_json::from_str(&fs::read_to_string(package.join("manifest.json")).unwrap()).unwrap();
+    assert_eq!(manifest["total_package_size"], "00000000000000000008");
+    assert_eq!(manifest["title"], "Airport");
+  }
+}
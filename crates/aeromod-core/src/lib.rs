@@ -1,5 +1,22 @@
+pub mod activation;
 pub mod addon;
+pub mod archive;
+pub mod baseline;
+pub mod community;
+pub mod content;
+pub mod dependency;
+pub mod export;
+pub mod flightplan;
+pub mod layout;
+mod library;
 pub mod manifest;
+pub mod metadata;
 pub mod preset;
+pub mod provenance;
+pub mod query;
+pub mod relocate;
 pub mod scenery;
 pub mod sim;
+#[cfg(test)]
+mod testing;
+pub mod version;
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::addon::INTERNAL_DIR_NAME;
+use crate::layout;
+use crate::manifest::Manifest;
+use crate::version::Version;
+
+const LIBRARY_INDEX_VERSION: u32 = 2;
+
+/// Manifest data and size of an addon directory, together with the
+/// modification times they were read at.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct IndexEntry {
+  modified: ModifiedTimes,
+  pub name: String,
+  pub creator: String,
+  pub version: Version,
+  pub content_type: String,
+  pub size: u64,
+}
+
+impl IndexEntry {
+  /// Reads the manifest and computes the size of the addon in `path`.
+  pub fn read(path: &Path) -> Result<Self> {
+    if !path.exists() {
+      return Err(anyhow!(
+        "Addon '{}' not found in addons directory",
+        path.file_name().unwrap_or_default().to_string_lossy()
+      ));
+    }
+
+    if !path.is_dir() {
+      return Err(anyhow!("Not a directory"));
+    };
+
+    let modified = modified_times(path);
+    let manifest =
+      Manifest::parse(path.join("manifest.json")).context("Failed to parse manifest.json")?;
+    let size = aeromod_fs::get_directory_size(path).unwrap_or(0);
+
+    Ok(Self {
+      modified,
+      name: manifest.title,
+      creator: manifest.creator,
+      version: manifest.package_version,
+      content_type: manifest.content_type,
+      size,
+    })
+  }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct IndexFile {
+  version: u32,
+  entries: HashMap<String, IndexEntry>,
+}
+
+/// Cache of [`IndexEntry`] per addon id, stored in the internal directory of
+/// the addons directory. An entry is reused as long as neither the addon
+/// directory nor its `manifest.json` or `layout.json` were modified since it
+/// was read. Changes deeper in the addon that leave these untouched have to
+/// [`invalidate`](Self::invalidate) the entry.
+pub(crate) struct LibraryIndex {
+  path: PathBuf,
+  entries: HashMap<String, IndexEntry>,
+}
+
+impl LibraryIndex {
+  /// Loads the index, starting empty if it is missing, unreadable or was
+  /// written by another version.
+  pub fn load(settings: &AppSettings) -> Self {
+    let path = index_path(settings);
+    let entries = File::open(&path)
+      .ok()
+      .and_then(|file| serde_json::from_reader::<_, IndexFile>(BufReader::new(file)).ok())
+      .filter(|index| index.version == LIBRARY_INDEX_VERSION)
+      .map(|index| index.entries)
+      .unwrap_or_default();
+
+    Self { path, entries }
+  }
+
+  /// Reads the given addons, reusing cached entries that are still current
+  /// and reading the others in parallel. The index is updated to hold
+  /// exactly the addons that were read successfully.
+  pub fn read_all(&mut self, addons_dir: &Path, ids: &[String]) -> Vec<Result<IndexEntry>> {
+    let entries = &self.entries;
+    let results = parallel_map(ids, |id| {
+      let path = addons_dir.join(id);
+      match entries.get(id) {
+        Some(entry) if entry.modified == modified_times(&path) => Ok(entry.clone()),
+        _ => IndexEntry::read(&path),
+      }
+    });
+
+    let mut changed = false;
+    let mut entries = HashMap::new();
+    for (id, result) in ids.iter().zip(&results) {
+      if let Ok(entry) = result {
+        changed |= self
+          .entries
+          .get(id)
+          .is_none_or(|e| e.modified != entry.modified);
+        entries.insert(id.clone(), entry.clone());
+      }
+    }
+    changed |= entries.len() != self.entries.len();
+    self.entries = entries;
+
+    if changed {
+      let _ = self.save();
+    }
+
+    results
+  }
+
+  /// Drops the cached entry of an addon so that it is read again by the next
+  /// scan.
+  pub fn invalidate(settings: &AppSettings, id: &str) -> Result<()> {
+    let mut index = Self::load(settings);
+    if index.entries.remove(id).is_some() {
+      index.save()?;
+    }
+
+    Ok(())
+  }
+
+  fn save(&self) -> Result<()> {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    let index = IndexFile {
+      version: LIBRARY_INDEX_VERSION,
+      entries: self.entries.clone(),
+    };
+    aeromod_fs::write_atomic(&self.path, |writer| {
+      serde_json::to_writer(writer, &index).map_err(Into::into)
+    })?;
+
+    Ok(())
+  }
+}
+
+fn index_path(settings: &AppSettings) -> PathBuf {
+  Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("library.json")
+}
+
+/// Modification times of the addon directory, its `manifest.json` and its
+/// `layout.json`.
+type ModifiedTimes = [u64; 3];
+
+fn modified_times(path: &Path) -> ModifiedTimes {
+  let modified = |p: &Path| fs::metadata(p).map(|m| layout::file_time(&m)).unwrap_or(0);
+  [
+    modified(path),
+    modified(&path.join("manifest.json")),
+    modified(&path.join("layout.json")),
+  ]
+}
+
+/// Maps `items` on all available cores, preserving their order.
+fn parallel_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+  T: Sync,
+  R: Send,
+  F: Fn(&T) -> R + Sync,
+{
+  let threads = thread::available_parallelism()
+    .map(|n| n.get())
+    .unwrap_or(1)
+    .min(items.len());
+  if threads <= 1 {
+    return items.iter().map(f).collect();
+  }
+
+  let next = AtomicUsize::new(0);
+  let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+
+  thread::scope(|scope| {
+    for _ in 0..threads {
+      scope.spawn(|| {
+        loop {
+          let i = next.fetch_add(1, Ordering::Relaxed);
+          let Some(item) = items.get(i) else {
+            break;
+          };
+
+          let result = f(item);
+          results.lock().unwrap()[i] = Some(result);
+        }
+      });
+    }
+  });
+
+  results
+    .into_inner()
+    .unwrap()
+    .into_iter()
+    .map(|r| r.expect("every item is mapped"))
+    .collect()
+}
@@ -1,32 +1,123 @@
-use std::fs::File;
-use std::io::BufReader;
+use std::fs;
 use std::path::Path;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::version::Version;
+
 #[derive(Serialize, Deserialize)]
 pub struct Manifest {
+  #[serde(default)]
   pub dependencies: Vec<Dependency>,
   pub content_type: String,
   pub title: String,
+  #[serde(default)]
   pub manufacturer: String,
+  #[serde(default)]
   pub creator: String,
-  pub package_version: String,
-  pub minimum_game_version: String,
+  #[serde(default)]
+  pub package_version: Version,
+  #[serde(default)]
+  pub minimum_game_version: Version,
+  #[serde(default)]
+  pub release_notes: ReleaseNotes,
+  #[serde(default)]
+  pub total_package_size: String,
+  #[serde(default)]
+  pub builder: String,
+  #[serde(default)]
+  pub package_order_hint: String,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Dependency {
   pub name: String,
-  pub package_version: String,
+  #[serde(default)]
+  pub package_version: Version,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ReleaseNotes {
+  #[serde(default)]
+  pub neutral: ReleaseNote,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct ReleaseNote {
+  #[serde(rename = "LastUpdate", default)]
+  pub last_update: String,
+  #[serde(rename = "OlderHistory", default)]
+  pub older_history: String,
 }
 
 impl Manifest {
   pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let manifest: Manifest = serde_json::from_reader(reader)?;
+    let data = fs::read_to_string(path)?;
+    // Some packaging tools write a UTF-8 byte order mark, which the sim
+    // accepts but serde_json does not.
+    let manifest: Manifest = serde_json::from_str(data.trim_start_matches('\u{feff}'))?;
     Ok(manifest)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_parse_minimal_manifest_with_bom() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("manifest.json");
+    fs::write(
+      &path,
+      "\u{feff}{\"content_type\": \"SCENERY\", \"title\": \"Minimal\", \"package_version\": \"1.0\"}",
+    )
+    .unwrap();
+
+    let manifest = Manifest::parse(&path).unwrap();
+    assert_eq!(manifest.title, "Minimal");
+    assert!(manifest.dependencies.is_empty());
+    assert!(manifest.creator.is_empty());
+    assert!(!manifest.minimum_game_version.is_numeric());
+  }
+
+  #[test]
+  fn test_parse_full_manifest() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("manifest.json");
+    let data = serde_json::json!({
+      "dependencies": [{ "name": "fs-base", "package_version": "0.1.0" }],
+      "content_type": "AIRCRAFT",
+      "title": "Full",
+      "manufacturer": "Manufacturer",
+      "creator": "Creator",
+      "package_version": "1.2.3",
+      "minimum_game_version": "1.37.19",
+      "release_notes": {
+        "neutral": { "LastUpdate": "Fixed gear", "OlderHistory": "Initial release" }
+      },
+      "total_package_size": "00000000000000012345",
+      "builder": "Microsoft Flight Simulator 2020",
+      "package_order_hint": "SCENERY",
+    });
+    fs::write(&path, data.to_string()).unwrap();
+
+    let manifest = Manifest::parse(&path).unwrap();
+    assert_eq!(manifest.dependencies[0].name, "fs-base");
+    assert_eq!(manifest.release_notes.neutral.last_update, "Fixed gear");
+    assert_eq!(manifest.total_package_size, "00000000000000012345");
+    assert_eq!(manifest.package_order_hint, "SCENERY");
+  }
+
+  #[test]
+  fn test_parse_reports_missing_required_fields() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("manifest.json");
+    fs::write(&path, "{\"title\": \"No type\"}").unwrap();
+
+    let error = Manifest::parse(&path).err().unwrap();
+    assert!(error.to_string().contains("content_type"));
+  }
+}
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::addon::Addon;
+
+/// Serializes changes to the store so that concurrent updates are not lost.
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Information the user attaches to an addon. Not part of the package.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonMetadata {
+  #[serde(default)]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub notes: String,
+  #[serde(default)]
+  pub favorite: bool,
+}
+
+impl AddonMetadata {
+  pub fn is_empty(&self) -> bool {
+    self.tags.is_empty() && self.notes.is_empty() && !self.favorite
+  }
+
+  pub fn has_tag(&self, tag: &str) -> bool {
+    self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+  }
+
+  /// Trims tags and drops empty and duplicate ones, ignoring case.
+  fn normalize(&mut self) {
+    let mut seen = BTreeSet::new();
+    self.tags = self
+      .tags
+      .iter()
+      .map(|t| t.trim().to_string())
+      .filter(|t| !t.is_empty() && seen.insert(t.to_lowercase()))
+      .collect();
+  }
+}
+
+/// User metadata of every addon, keyed by addon id and kept in a single file
+/// in the config directory.
+pub struct MetadataStore {
+  path: PathBuf,
+  addons: BTreeMap<String, AddonMetadata>,
+}
+
+impl MetadataStore {
+  /// Opens the store at `path`. A missing file is an empty store.
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let path = path.as_ref().to_path_buf();
+    let addons = if path.exists() {
+      let file = File::open(&path)?;
+      serde_json::from_reader(BufReader::new(file)).context("Failed to parse addon metadata")?
+    } else {
+      BTreeMap::new()
+    };
+
+    Ok(Self { path, addons })
+  }
+
+  /// Opens the store in the config directory of `settings`.
+  pub fn open(settings: &AppSettings) -> Result<Self> {
+    Self::load(metadata_path(settings)?)
+  }
+
+  /// Loads the store in the config directory, applies `f` and saves it.
+  /// Updates are serialized so that concurrent changes are not lost.
+  pub fn update<F>(settings: &AppSettings, f: F) -> Result<()>
+  where
+    F: FnOnce(&mut Self),
+  {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut store = Self::open(settings)?;
+    f(&mut store);
+    store.save()
+  }
+
+  pub fn save(&self) -> Result<()> {
+    if let Some(parent) = self.path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    aeromod_fs::write_atomic(&self.path, |writer| {
+      serde_json::to_writer_pretty(writer, &self.addons).map_err(Into::into)
+    })?;
+
+    Ok(())
+  }
+
+  pub fn get(&self, id: &str) -> AddonMetadata {
+    self.addons.get(id).cloned().unwrap_or_default()
+  }
+
+  pub fn set(&mut self, id: &str, mut metadata: AddonMetadata) {
+    metadata.normalize();
+    if metadata.is_empty() {
+      self.addons.remove(id);
+    } else {
+      self.addons.insert(id.to_string(), metadata);
+    }
+  }
+
+  pub fn rename(&mut self, id: &str, new_id: &str) {
+    if let Some(metadata) = self.addons.remove(id) {
+      self.addons.insert(new_id.to_string(), metadata);
+    }
+  }
+
+  pub fn remove(&mut self, id: &str) {
+    self.addons.remove(id);
+  }
+
+  /// Every tag in use, sorted.
+  pub fn tags(&self) -> BTreeSet<String> {
+    self
+      .addons
+      .values()
+      .flat_map(|m| m.tags.iter().cloned())
+      .collect()
+  }
+
+  /// Ids of the addons carrying `tag`, compared without case.
+  pub fn addons_with_tag(&self, tag: &str) -> Vec<String> {
+    self
+      .addons
+      .iter()
+      .filter(|(_, m)| m.has_tag(tag))
+      .map(|(id, _)| id.clone())
+      .collect()
+  }
+
+  /// Attaches the stored metadata to each addon.
+  pub fn apply(&self, addons: &mut [Addon]) {
+    for addon in addons {
+      addon.metadata = self.get(&addon.id);
+    }
+  }
+}
+
+fn metadata_path(settings: &AppSettings) -> Result<PathBuf> {
+  let config_dir = settings
+    .config_dir
+    .as_ref()
+    .ok_or_else(|| anyhow!("The config directory is not set"))?;
+
+  Ok(config_dir.join("metadata.json"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_metadata_store_round_trip() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("metadata.json");
+
+    let mut store = MetadataStore::load(&path).unwrap();
+    store.set(
+      "airport-ltfm",
+      AddonMetadata {
+        tags: vec!["EU".into(), " eu ".into(), "event-2026".into(), "".into()],
+        notes: "Night lighting is broken".into(),
+        favorite: true,
+      },
+    );
+    store.set("aircraft-a320", AddonMetadata::default());
+    store.rename("airport-ltfm", "airport-ltfm-v2");
+    store.save().unwrap();
+
+    let mut store = MetadataStore::load(&path).unwrap();
+    let metadata = store.get("airport-ltfm-v2");
+    assert_eq!(metadata.tags, ["EU", "event-2026"]);
+    assert!(metadata.favorite);
+    assert!(store.get("airport-ltfm").is_empty());
+    assert!(store.get("aircraft-a320").is_empty());
+    assert_eq!(store.addons_with_tag("eu"), ["airport-ltfm-v2"]);
+
+    store.remove("airport-ltfm-v2");
+    assert!(store.tags().is_empty());
+  }
+
+  #[test]
+  fn test_concurrent_updates_are_kept() {
+    let (_dir, settings) = crate::testing::settings();
+
+    std::thread::scope(|scope| {
+      for i in 0..8 {
+        let settings = &settings;
+        scope.spawn(move || {
+          MetadataStore::update(settings, |store| {
+            store.set(
+              &format!("addon-{}", i),
+              AddonMetadata {
+                favorite: true,
+                ..Default::default()
+              },
+            )
+          })
+          .unwrap();
+        });
+      }
+    });
+
+    let store = MetadataStore::open(&settings).unwrap();
+    assert!((0..8).all(|i| store.get(&format!("addon-{}", i)).favorite));
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::fs::File;
 use std::path::{Path, PathBuf};
@@ -8,6 +8,10 @@ use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 
 use crate::addon;
+use crate::content;
+use crate::dependency::PackageIndex;
+use crate::metadata::MetadataStore;
+use crate::sim;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Preset {
@@ -15,11 +19,72 @@ pub struct Preset {
   pub name: String,
   pub description: Option<String>,
   pub addons: Vec<String>,
+  /// Addons carrying any of these tags are part of the preset as well.
+  #[serde(default)]
+  pub tags: Vec<String>,
+  /// Official and marketplace packages to activate or deactivate, by id.
+  /// Packages not listed keep their current state.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub official_packages: BTreeMap<String, bool>,
 }
 
 impl Preset {
-  pub fn apply(&self, settings: &AppSettings) -> Result<()> {
-    let addons_in_preset: HashSet<_> = self.addons.iter().collect();
+  /// Returns the addons listed in the preset followed by the addons matching
+  /// its tags, without duplicates.
+  pub fn resolve_addons(&self, metadata: &MetadataStore) -> Vec<String> {
+    let mut seen = HashSet::new();
+    self
+      .addons
+      .iter()
+      .cloned()
+      .chain(self.tags.iter().flat_map(|t| metadata.addons_with_tag(t)))
+      .filter(|id| seen.insert(id.clone()))
+      .collect()
+  }
+
+  /// Returns the installed addons that the addons in the preset depend on,
+  /// directly or transitively, but that the preset does not include.
+  pub fn missing_dependencies(
+    &self,
+    settings: &AppSettings,
+    metadata: &MetadataStore,
+  ) -> Result<Vec<String>> {
+    let addons = self.resolve_addons(metadata);
+    let index = PackageIndex::load(settings)?;
+
+    Ok(
+      index
+        .managed_dependencies(addons.iter().map(String::as_str))
+        .into_iter()
+        .filter(|id| !addons.contains(id))
+        .collect(),
+    )
+  }
+
+  /// Enables exactly the addons in the preset and disables everything else.
+  /// With `with_dependencies`, the installed addons they depend on are
+  /// enabled as well and returned. Official packages listed in the preset
+  /// are set to their state in `Content.xml`.
+  pub fn apply(
+    &self,
+    settings: &AppSettings,
+    metadata: &MetadataStore,
+    with_dependencies: bool,
+  ) -> Result<Vec<String>> {
+    // Checked before any addon is touched so that a running sim does not
+    // leave the preset half applied.
+    if !self.official_packages.is_empty() && sim::is_sim_running() {
+      bail!("Close the sim before applying a preset with official packages");
+    }
+
+    let addons = self.resolve_addons(metadata);
+    let dependencies = if with_dependencies {
+      self.missing_dependencies(settings, metadata)?
+    } else {
+      Vec::new()
+    };
+
+    let addons_in_preset: HashSet<_> = addons.iter().chain(&dependencies).collect();
     let current_addons = addon::get_addons(settings)?;
 
     for a in current_addons {
@@ -33,7 +98,11 @@ impl Preset {
       }
     }
 
-    Ok(())
+    if !self.official_packages.is_empty() {
+      content::set_official_packages_active(settings, &self.official_packages)?;
+    }
+
+    Ok(dependencies)
   }
 }
 
@@ -59,6 +128,8 @@ impl PresetManager {
       name: preset.name,
       description: preset.description,
       addons: preset.addons,
+      tags: preset.tags,
+      official_packages: preset.official_packages,
     };
 
     let preset_path = self.preset_file(&new_preset.id);
@@ -124,7 +195,7 @@ impl PresetManager {
   }
 }
 
-fn slugify(name: &str) -> String {
+pub(crate) fn slugify(name: &str) -> String {
   name
     .trim()
     .to_lowercase()
@@ -149,4 +220,32 @@ mod tests {
     assert!(non_existent_path.exists());
     assert!(non_existent_path.is_dir());
   }
+
+  #[test]
+  fn test_resolve_addons_includes_tagged_addons() {
+    let dir = tempdir().unwrap();
+    let mut metadata = MetadataStore::load(dir.path().join("metadata.json")).unwrap();
+    for id in ["airport-ltfm", "airport-eddf"] {
+      metadata.set(
+        id,
+        crate::metadata::AddonMetadata {
+          tags: vec!["EU".to_string()],
+          ..Default::default()
+        },
+      );
+    }
+
+    let preset = Preset {
+      id: "europe".to_string(),
+      name: "Europe".to_string(),
+      description: None,
+      addons: vec!["aircraft-a320".to_string(), "airport-ltfm".to_string()],
+      tags: vec!["eu".to_string()],
+      official_packages: BTreeMap::new(),
+    };
+    assert_eq!(
+      preset.resolve_addons(&metadata),
+      ["aircraft-a320", "airport-ltfm", "airport-eddf"]
+    );
+  }
 }
@@ -0,0 +1,74 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::addon::INTERNAL_DIR_NAME;
+use crate::version::Version;
+
+/// Where an installed addon came from. Kept in the internal directory of the
+/// addons directory so the package itself is never modified.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Provenance {
+  /// File or folder name the addon was installed from.
+  pub source_file: String,
+  /// SHA-256 of the archive, if the addon was installed from one.
+  pub archive_hash: Option<String>,
+  /// Install time in seconds since the Unix epoch.
+  pub installed_at: u64,
+  pub installer_version: Option<String>,
+  /// Version that was replaced, if the install updated an existing addon.
+  pub previous_version: Option<Version>,
+}
+
+impl Provenance {
+  pub fn new(source: &Path) -> Self {
+    Self {
+      source_file: source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+      archive_hash: None,
+      installed_at: SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0),
+      installer_version: None,
+      previous_version: None,
+    }
+  }
+
+  pub fn load(settings: &AppSettings, id: &str) -> Result<Option<Self>> {
+    let path = provenance_path(settings, id);
+    if !path.exists() {
+      return Ok(None);
+    }
+
+    let file = File::open(path)?;
+    Ok(Some(serde_json::from_reader(BufReader::new(file))?))
+  }
+
+  pub fn save(&self, settings: &AppSettings, id: &str) -> Result<()> {
+    let path = provenance_path(settings, id);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    aeromod_fs::write_atomic(path, |writer| {
+      serde_json::to_writer_pretty(writer, self).map_err(Into::into)
+    })
+    .context("Failed to write install metadata")
+  }
+}
+
+pub(crate) fn provenance_path(settings: &AppSettings, id: &str) -> PathBuf {
+  Path::new(&settings.addons_dir)
+    .join(INTERNAL_DIR_NAME)
+    .join("provenance")
+    .join(format!("{}.json", id))
+}
@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::addon::Addon;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+  /// Best text match first, then by name. Same as `Name` without a text.
+  #[default]
+  Relevance,
+  Id,
+  Name,
+  Creator,
+  Version,
+  ContentType,
+  Size,
+  Enabled,
+  Favorite,
+}
+
+/// Search, filter, sort and pagination options for the addon list. Every
+/// filter is optional; an empty query returns all addons sorted by name.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AddonQuery {
+  /// Fuzzy matched against name, creator and id.
+  pub text: Option<String>,
+  /// Content types to include, compared without case.
+  pub content_types: Vec<String>,
+  pub enabled: Option<bool>,
+  pub favorite: Option<bool>,
+  pub min_size: Option<u64>,
+  pub max_size: Option<u64>,
+  /// Tags the addon must all carry, compared without case.
+  pub tags: Vec<String>,
+  pub sort: SortField,
+  pub descending: bool,
+  pub offset: usize,
+  pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddonPage {
+  pub addons: Vec<Addon>,
+  /// Number of addons matching the query before pagination.
+  pub total: usize,
+}
+
+impl AddonQuery {
+  pub fn run(&self, addons: Vec<Addon>) -> AddonPage {
+    let text = self
+      .text
+      .as_deref()
+      .map(str::trim)
+      .filter(|t| !t.is_empty());
+
+    let mut matches: Vec<(u32, Addon)> = addons
+      .into_iter()
+      .filter(|a| self.matches_filters(a))
+      .filter_map(|a| match text {
+        Some(text) => match_score(text, &a).map(|score| (score, a)),
+        None => Some((0, a)),
+      })
+      .collect();
+
+    matches.sort_by(|(score_a, a), (score_b, b)| {
+      let ordering = match self.sort {
+        SortField::Relevance => score_b.cmp(score_a),
+        SortField::Id => a.id.cmp(&b.id),
+        SortField::Name => Ordering::Equal,
+        SortField::Creator => compare_text(&a.creator, &b.creator),
+        SortField::Version => a.version.cmp(&b.version),
+        SortField::ContentType => compare_text(&a.content_type, &b.content_type),
+        SortField::Size => a.size.cmp(&b.size),
+        SortField::Enabled => a.enabled.cmp(&b.enabled),
+        SortField::Favorite => a.metadata.favorite.cmp(&b.metadata.favorite),
+      }
+      .then_with(|| compare_text(&a.name, &b.name))
+      .then_with(|| a.id.cmp(&b.id));
+
+      if self.descending {
+        ordering.reverse()
+      } else {
+        ordering
+      }
+    });
+
+    let total = matches.len();
+    let addons = matches
+      .into_iter()
+      .map(|(_, a)| a)
+      .skip(self.offset)
+      .take(self.limit.unwrap_or(usize::MAX))
+      .collect();
+
+    AddonPage { addons, total }
+  }
+
+  fn matches_filters(&self, addon: &Addon) -> bool {
+    (self.content_types.is_empty()
+      || self
+        .content_types
+        .iter()
+        .any(|t| t.eq_ignore_ascii_case(&addon.content_type)))
+      && self.enabled.is_none_or(|e| addon.enabled == e)
+      && self.favorite.is_none_or(|f| addon.metadata.favorite == f)
+      && self.min_size.is_none_or(|min| addon.size >= min)
+      && self.max_size.is_none_or(|max| addon.size <= max)
+      && self.tags.iter().all(|t| addon.metadata.has_tag(t))
+  }
+}
+
+fn compare_text(a: &str, b: &str) -> Ordering {
+  a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// Best score of `text` against the name, creator and id of an addon, or
+/// `None` if it matches none of them.
+fn match_score(text: &str, addon: &Addon) -> Option<u32> {
+  [&addon.name, &addon.creator, &addon.id]
+    .into_iter()
+    .filter_map(|field| fuzzy_score(text, field))
+    .max()
+}
+
+/// Scores `pattern` as a case-insensitive subsequence of `value`. Substring
+/// matches score highest, prefixes above other substrings; subsequence
+/// matches lose points for every character skipped between matches.
+fn fuzzy_score(pattern: &str, value: &str) -> Option<u32> {
+  let pattern = pattern.to_lowercase();
+  let value = value.to_lowercase();
+
+  if let Some(position) = value.find(&pattern) {
+    let bonus = if position == 0 { 200 } else { 100 };
+    return Some(1000 + bonus - position.min(100) as u32);
+  }
+
+  let mut chars = value.chars();
+  let mut gaps = 0u32;
+  for p in pattern.chars() {
+    let mut skipped = 0;
+    loop {
+      let c = chars.next()?;
+      if c == p {
+        break;
+      }
+      skipped += 1;
+    }
+    gaps += skipped;
+  }
+
+  Some(500u32.saturating_sub(gaps))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::metadata::AddonMetadata;
+  use crate::version::Version;
+
+  fn addon(id: &str, name: &str, creator: &str, size: u64, tags: &[&str]) -> Addon {
+    Addon {
+      id: id.to_string(),
+      name: name.to_string(),
+      creator: creator.to_string(),
+      version: Version::parse("1.0.0"),
+      content_type: "SCENERY".to_string(),
+      enabled: false,
+      size,
+      scenery: None,
+      provenance: None,
+      metadata: AddonMetadata {
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+      },
+    }
+  }
+
+  fn library() -> Vec<Addon> {
+    vec![
+      addon(
+        "airport-ltfm",
+        "Istanbul Airport",
+        "Creator A",
+        300,
+        &["EU"],
+      ),
+      addon(
+        "airport-eddf",
+        "Frankfurt",
+        "Creator B",
+        200,
+        &["EU", "WIP"],
+      ),
+      addon("airport-kjfk", "New York JFK", "Creator A", 100, &[]),
+    ]
+  }
+
+  fn ids(page: &AddonPage) -> Vec<&str> {
+    page.addons.iter().map(|a| a.id.as_str()).collect()
+  }
+
+  #[test]
+  fn test_fuzzy_text_search_ranks_substrings_first() {
+    let query = AddonQuery {
+      text: Some("frt".to_string()),
+      ..Default::default()
+    };
+    assert_eq!(ids(&query.run(library())), ["airport-eddf"]);
+
+    let query = AddonQuery {
+      text: Some("airport".to_string()),
+      ..Default::default()
+    };
+    // Every id starts with "airport", the Istanbul name contains it as well.
+    assert_eq!(query.run(library()).total, 3);
+    assert!(fuzzy_score("ist", "istanbul") > fuzzy_score("stn", "istanbul"));
+    assert!(fuzzy_score("xyz", "istanbul").is_none());
+  }
+
+  #[test]
+  fn test_sort_by_relevance() {
+    // "Frankfurt" contains the text, the other addons only match it as a
+    // subsequence of their creator.
+    // Equal scores are sorted by name.
+    let mut query = AddonQuery {
+      text: Some("ra".to_string()),
+      ..Default::default()
+    };
+    assert_eq!(
+      ids(&query.run(library())),
+      ["airport-eddf", "airport-ltfm", "airport-kjfk"]
+    );
+
+    query.limit = Some(1);
+    assert_eq!(ids(&query.run(library())), ["airport-eddf"]);
+
+    query.limit = None;
+    query.descending = true;
+    assert_eq!(
+      ids(&query.run(library())),
+      ["airport-kjfk", "airport-ltfm", "airport-eddf"]
+    );
+  }
+
+  #[test]
+  fn test_filters_sort_and_pagination() {
+    let query = AddonQuery {
+      tags: vec!["eu".to_string()],
+      max_size: Some(250),
+      ..Default::default()
+    };
+    assert_eq!(ids(&query.run(library())), ["airport-eddf"]);
+
+    let query = AddonQuery {
+      sort: SortField::Size,
+      descending: true,
+      offset: 1,
+      limit: Some(1),
+      ..Default::default()
+    };
+    let page = query.run(library());
+    assert_eq!(ids(&page), ["airport-eddf"]);
+    assert_eq!(page.total, 3);
+
+    let query = AddonQuery::default();
+    assert_eq!(
+      ids(&query.run(library())),
+      ["airport-eddf", "airport-ltfm", "airport-kjfk"]
+    );
+  }
+}
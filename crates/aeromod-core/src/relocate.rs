@@ -0,0 +1,325 @@
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use aeromod_settings::AppSettings;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::activation;
+use crate::addon::INTERNAL_DIR_NAME;
+use crate::community;
+
+const JOURNAL_NAME: &str = "relocate.json";
+
+/// Written to the old addons directory while a relocation is in progress, so
+/// an interrupted relocation can be detected and resumed.
+#[derive(Serialize, Deserialize)]
+struct RelocateJournal {
+  to: PathBuf,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RelocateProgress {
+  /// Entry that was just moved.
+  pub id: String,
+  pub done: usize,
+  pub total: usize,
+}
+
+fn journal_path(addons_dir: &Path) -> PathBuf {
+  addons_dir.join(INTERNAL_DIR_NAME).join(JOURNAL_NAME)
+}
+
+/// Returns the target of an interrupted relocation of the addons directory.
+pub fn pending_relocation(settings: &AppSettings) -> Option<PathBuf> {
+  let file = File::open(journal_path(&settings.addons_dir)).ok()?;
+  let journal: RelocateJournal = serde_json::from_reader(BufReader::new(file)).ok()?;
+  Some(journal.to)
+}
+
+/// Moves every addon from the addons directory to `new_dir`, rewrites the
+/// community links of enabled addons and returns the settings pointing at the
+/// new location. The caller is responsible for saving them.
+///
+/// Each addon is moved and relinked before the next one is started, so an
+/// interruption leaves every addon usable in either location. Calling this
+/// again with the same `new_dir` resumes the relocation; any other `new_dir`
+/// is refused until it has completed.
+pub fn relocate_library<F>(
+  settings: &AppSettings,
+  new_dir: &Path,
+  mut on_progress: F,
+) -> Result<AppSettings>
+where
+  F: FnMut(&RelocateProgress),
+{
+  let old_dir = settings.addons_dir.as_path();
+  let canonical_old_dir = community::canonical_path(old_dir);
+  let canonical_new_dir = community::canonical_path(new_dir);
+  if canonical_new_dir.starts_with(&canonical_old_dir)
+    || canonical_old_dir.starts_with(&canonical_new_dir)
+  {
+    return Err(anyhow!(
+      "The new addons directory must not be inside the current one or contain it"
+    ));
+  }
+
+  let pending = pending_relocation(settings);
+  if let Some(to) = pending
+    .as_ref()
+    .filter(|to| !community::same_path(to, new_dir))
+  {
+    return Err(anyhow!(
+      "A relocation to '{}' was interrupted, resume it before moving the library elsewhere",
+      to.display()
+    ));
+  }
+
+  let resuming = pending.is_some();
+  let is_empty = fs::read_dir(new_dir)
+    .map(|mut entries| entries.next().is_none())
+    .unwrap_or(true);
+  if !resuming && !is_empty {
+    return Err(anyhow!("Directory '{}' is not empty", new_dir.display()));
+  }
+
+  let internal_dir = old_dir.join(INTERNAL_DIR_NAME);
+  fs::create_dir_all(&internal_dir)?;
+  fs::create_dir_all(new_dir)?;
+  aeromod_fs::write_atomic(journal_path(old_dir), |writer| {
+    serde_json::to_writer(
+      writer,
+      &RelocateJournal {
+        to: new_dir.to_path_buf(),
+      },
+    )
+    .map_err(Into::into)
+  })?;
+
+  let staging_dir = new_dir.join(INTERNAL_DIR_NAME).join("staging");
+  let mut ids = Vec::new();
+  for entry in fs::read_dir(old_dir)? {
+    let name = entry?.file_name();
+    if name != INTERNAL_DIR_NAME {
+      ids.push(name.to_string_lossy().into_owned());
+    }
+  }
+  ids.sort();
+
+  let total = ids.len();
+  for (i, id) in ids.into_iter().enumerate() {
+    let src = old_dir.join(&id);
+    let dst = new_dir.join(&id);
+    move_entry(&src, &dst, &staging_dir.join(&id))
+      .with_context(|| format!("Failed to move '{}'", id))?;
+
+    let link = Path::new(&settings.community_dir).join(&id);
+    if community::link_target(&link).is_some_and(|target| community::same_path(&target, &src)) {
+      aeromod_fs::remove_symlink_dir(&link)?;
+      aeromod_fs::symlink_dir(&dst, &link).context("Failed to create symlink")?;
+    } else if activation::active_mode(settings, &id).is_some() {
+      // Hard-linked and copied addons only need to know their new source.
+      activation::retarget(&link, &dst)?;
+    }
+
+    on_progress(&RelocateProgress {
+      id,
+      done: i + 1,
+      total,
+    });
+  }
+
+  // Links left dangling by an earlier, interrupted run whose entry has
+  // already been moved.
+  for entry in fs::read_dir(&settings.community_dir)? {
+    let link = entry?.path();
+    let Some(target) = community::link_target(&link) else {
+      continue;
+    };
+    let target = community::canonical_path(&target);
+    let Ok(relative) = target.strip_prefix(&canonical_old_dir) else {
+      continue;
+    };
+
+    let dst = new_dir.join(relative);
+    if !target.exists() && dst.exists() {
+      aeromod_fs::remove_symlink_dir(&link)?;
+      aeromod_fs::symlink_dir(&dst, &link).context("Failed to create symlink")?;
+    }
+  }
+
+  merge_internal_dir(&internal_dir, &new_dir.join(INTERNAL_DIR_NAME))?;
+  let _ = fs::remove_dir_all(&staging_dir);
+  fs::remove_file(journal_path(old_dir))?;
+  let _ = fs::remove_dir(&internal_dir);
+
+  Ok(AppSettings {
+    addons_dir: new_dir.to_path_buf(),
+    ..settings.clone()
+  })
+}
+
+/// Moves `src` to `dst`, renaming when possible and copying through
+/// `staging` otherwise. If `dst` already exists, `src` is the leftover of an
+/// interrupted move whose copy had completed.
+fn move_entry(src: &Path, dst: &Path, staging: &Path) -> Result<()> {
+  if dst.exists() {
+    if src.exists() {
+      remove_entry(src)?;
+    }
+    return Ok(());
+  }
+
+  if fs::rename(src, dst).is_ok() {
+    return Ok(());
+  }
+
+  if staging.exists() {
+    remove_entry(staging)?;
+  }
+  if let Some(parent) = staging.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  if src.is_dir() {
+    aeromod_fs::copy_dir_all(src, staging)?;
+  } else {
+    fs::copy(src, staging)?;
+  }
+  fs::rename(staging, dst)?;
+  remove_entry(src)
+}
+
+fn remove_entry(path: &Path) -> Result<()> {
+  if path.is_dir() {
+    fs::remove_dir_all(path)?;
+  } else {
+    fs::remove_file(path)?;
+  }
+  Ok(())
+}
+
+/// Moves AeroMod's own data (baselines, archives, install metadata) into the
+/// internal directory of the new location, keeping files that already exist
+/// there.
+fn merge_internal_dir(from: &Path, to: &Path) -> Result<()> {
+  fs::create_dir_all(to)?;
+
+  for entry in fs::read_dir(from)? {
+    let entry = entry?;
+    let name = entry.file_name();
+    if name == JOURNAL_NAME || name == "staging" {
+      continue;
+    }
+
+    let src = entry.path();
+    let dst = to.join(&name);
+    if entry.file_type()?.is_dir() {
+      merge_internal_dir(&src, &dst)?;
+      let _ = fs::remove_dir(&src);
+    } else {
+      move_entry(
+        &src,
+        &dst,
+        &to.join(format!("{}.tmp", name.to_string_lossy())),
+      )?;
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::addon;
+  use crate::testing::write_package;
+
+  #[test]
+  fn test_relocate_library_moves_addons_and_links() {
+    let (dir, settings) = crate::testing::settings();
+    for id in ["airport-ltfm", "airport-eddf", "airport-kjfk"] {
+      write_package(&settings.addons_dir.join(id), "Addon");
+    }
+    addon::enable_addon(&settings, "airport-ltfm").unwrap();
+    addon::enable_addon(&settings, "airport-eddf").unwrap();
+    crate::baseline::record_baseline(&settings, "airport-ltfm").unwrap();
+
+    // Simulate a run that was interrupted after moving the first addon.
+    let new_dir = dir.path().join("new-drive").join("addons");
+    fs::create_dir_all(settings.addons_dir.join(INTERNAL_DIR_NAME)).unwrap();
+    fs::write(
+      journal_path(&settings.addons_dir),
+      serde_json::to_string(&RelocateJournal {
+        to: new_dir.clone(),
+      })
+      .unwrap(),
+    )
+    .unwrap();
+    fs::create_dir_all(&new_dir).unwrap();
+    fs::rename(
+      settings.addons_dir.join("airport-eddf"),
+      new_dir.join("airport-eddf"),
+    )
+    .unwrap();
+    assert_eq!(pending_relocation(&settings), Some(new_dir.clone()));
+    let other_dir = dir.path().join("other-drive").join("addons");
+    assert!(relocate_library(&settings, &other_dir, |_| {}).is_err());
+    assert!(!other_dir.exists());
+
+    let mut progress = Vec::new();
+    let relocated = relocate_library(&settings, &new_dir, |p| progress.push(p.done)).unwrap();
+    assert_eq!(progress, [1, 2]);
+    assert_eq!(relocated.addons_dir, new_dir);
+    assert!(pending_relocation(&relocated).is_none());
+    assert!(pending_relocation(&settings).is_none());
+
+    let library = addon::scan_addons(&relocated).unwrap();
+    assert_eq!(library.addons.len(), 3);
+    assert!(
+      addon::parse_addon(&relocated, "airport-ltfm")
+        .unwrap()
+        .enabled
+    );
+    assert!(
+      addon::parse_addon(&relocated, "airport-eddf")
+        .unwrap()
+        .enabled
+    );
+    assert!(
+      !addon::parse_addon(&relocated, "airport-kjfk")
+        .unwrap()
+        .enabled
+    );
+    assert!(
+      crate::baseline::Baseline::load(&relocated, "airport-ltfm")
+        .unwrap()
+        .is_some()
+    );
+    assert_eq!(fs::read_dir(&settings.addons_dir).unwrap().count(), 0);
+  }
+
+  #[test]
+  fn test_relocate_library_rejects_nested_dirs() {
+    let (dir, mut settings) = crate::testing::settings();
+    let addons_dir = settings.addons_dir.clone();
+    fs::create_dir_all(dir.path().join("other")).unwrap();
+
+    // The same directory spelled differently.
+    settings.addons_dir = dir
+      .path()
+      .join("other")
+      .join("..")
+      .join(addons_dir.file_name().unwrap());
+    assert!(relocate_library(&settings, &addons_dir.join("nested"), |_| {}).is_err());
+
+    #[cfg(unix)]
+    {
+      let link = dir.path().join("link");
+      std::os::unix::fs::symlink(&addons_dir, &link).unwrap();
+      assert!(relocate_library(&settings, &link.join("nested"), |_| {}).is_err());
+    }
+  }
+}
@@ -1,22 +1,48 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
 use std::fs::File;
-use std::io::{BufReader, BufWriter};
+use std::io::{BufRead, BufReader, Lines, Write};
 use std::path::{Path, PathBuf};
 
 use aeromod_bgl::{BglObject, load_bgl_objects};
 use aeromod_settings::AppSettings;
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use crate::addon;
+use crate::manifest::Manifest;
+use crate::sim::SimManager;
 
 const BLOCKED_CREATORS: &[&str] = &["Navigraph"];
 
+/// Name prefixes of the packages that ship with the sim itself. Any other
+/// official package was bought from the marketplace.
+const STOCK_PACKAGE_PREFIXES: &[&str] = &["asobo-", "microsoft-", "fs-base", "fs20-", "fs24-"];
+
+/// On-disk format version of the scenery cache. Bump this whenever the
+/// cached records change so stale caches are discarded and rebuilt.
+pub const SCENERY_CACHE_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize)]
+struct SceneryCacheHeader {
+  version: u32,
+  addon_count: usize,
+  airport_count: usize,
+  official_airport_count: usize,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SceneryCache {
   #[serde(skip)]
   path: PathBuf,
+  addons: Vec<SceneryAddonStats>,
   airports: Vec<AirportScenery>,
+  /// Airports from the sim's official and marketplace packages, indexed only
+  /// when `index_official_packages` is enabled. `addon_id` holds the official
+  /// package name.
+  official_airports: Vec<AirportScenery>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -30,40 +56,182 @@ pub struct AirportScenery {
   pub runway_count: u8,
 }
 
+/// BGL scan statistics recorded for each scenery addon during a build.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SceneryAddonStats {
+  pub addon_id: String,
+  pub bgl_count: usize,
+  pub bgl_failures: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+  pub min_latitude: f64,
+  pub min_longitude: f64,
+  pub max_latitude: f64,
+  pub max_longitude: f64,
+}
+
+impl BoundingBox {
+  fn from_point(latitude: f64, longitude: f64) -> Self {
+    Self {
+      min_latitude: latitude,
+      min_longitude: longitude,
+      max_latitude: latitude,
+      max_longitude: longitude,
+    }
+  }
+
+  fn extend(&mut self, latitude: f64, longitude: f64) {
+    self.min_latitude = self.min_latitude.min(latitude);
+    self.min_longitude = self.min_longitude.min(longitude);
+    self.max_latitude = self.max_latitude.max(latitude);
+    self.max_longitude = self.max_longitude.max(longitude);
+  }
+}
+
+/// Aggregated view of the airports a single scenery addon provides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AddonScenerySummary {
+  pub addon_id: String,
+  pub airport_count: usize,
+  pub icaos: Vec<String>,
+  pub bounds: Option<BoundingBox>,
+  pub bgl_count: usize,
+  pub bgl_failures: usize,
+}
+
+impl AddonScenerySummary {
+  fn empty(addon_id: &str) -> Self {
+    Self {
+      addon_id: addon_id.to_string(),
+      airport_count: 0,
+      icaos: Vec::new(),
+      bounds: None,
+      bgl_count: 0,
+      bgl_failures: 0,
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageSource {
+  Stock,
+  Marketplace,
+}
+
+impl PackageSource {
+  pub fn of(package_id: &str) -> Self {
+    let package_id = package_id.to_lowercase();
+    if STOCK_PACKAGE_PREFIXES
+      .iter()
+      .any(|prefix| package_id.starts_with(prefix))
+    {
+      PackageSource::Stock
+    } else {
+      PackageSource::Marketplace
+    }
+  }
+}
+
+/// A community addon airport that replaces an airport from an official
+/// package.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AirportOverride {
+  pub addon_id: String,
+  pub icao: String,
+  pub package_id: String,
+  pub source: PackageSource,
+}
+
 impl SceneryCache {
   pub fn new<P: AsRef<Path>>(path: P) -> Self {
     Self {
       path: path.as_ref().to_path_buf(),
+      addons: Vec::new(),
       airports: Vec::new(),
+      official_airports: Vec::new(),
+    }
+  }
+
+  #[cfg(test)]
+  pub(crate) fn from_airports(airports: Vec<AirportScenery>) -> Self {
+    Self {
+      airports,
+      ..Self::new("scenery.json")
     }
   }
 
+  /// Loads a cache written by [`SceneryCache::save`].
+  ///
+  /// The file starts with a header line carrying the format version and
+  /// record counts, followed by one compact JSON record per line: first the
+  /// addon statistics, then the community airports and finally the official
+  /// package airports. A missing file yields an empty
+  /// cache; a version mismatch or malformed record is an error, so callers
+  /// can fall back to an empty cache and rebuild.
   pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
     let path = path.as_ref();
     if !path.exists() {
-      return Ok(Self {
-        path: path.to_path_buf(),
-        airports: Vec::new(),
-      });
+      return Ok(Self::new(path));
     }
 
     let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let mut scenery_cache: Self = serde_json::from_reader(reader)?;
-    scenery_cache.path = path.to_path_buf();
+    let mut lines = BufReader::new(file).lines();
 
-    Ok(scenery_cache)
+    let header = lines.next().context("Scenery cache is empty")??;
+    let header: SceneryCacheHeader =
+      serde_json::from_str(&header).context("Invalid scenery cache header")?;
+    if header.version != SCENERY_CACHE_VERSION {
+      bail!(
+        "Unsupported scenery cache version {} (expected {})",
+        header.version,
+        SCENERY_CACHE_VERSION
+      );
+    }
+
+    let addons = read_records(&mut lines, header.addon_count)?;
+    let airports = read_records(&mut lines, header.airport_count)?;
+    let official_airports = read_records(&mut lines, header.official_airport_count)?;
+
+    Ok(Self {
+      path: path.to_path_buf(),
+      addons,
+      airports,
+      official_airports,
+    })
   }
 
   pub fn save(&self) -> Result<()> {
-    let file = File::create(&self.path)?;
-    let writer = BufWriter::new(file);
-    serde_json::to_writer_pretty(writer, self)?;
+    let header = SceneryCacheHeader {
+      version: SCENERY_CACHE_VERSION,
+      addon_count: self.addons.len(),
+      airport_count: self.airports.len(),
+      official_airport_count: self.official_airports.len(),
+    };
+
+    aeromod_fs::write_atomic(&self.path, |writer| {
+      serde_json::to_writer(&mut *writer, &header)?;
+      writer.write_all(b"\n")?;
+      for addon in &self.addons {
+        serde_json::to_writer(&mut *writer, addon)?;
+        writer.write_all(b"\n")?;
+      }
+      for airport in self.airports.iter().chain(&self.official_airports) {
+        serde_json::to_writer(&mut *writer, airport)?;
+        writer.write_all(b"\n")?;
+      }
+      Ok(())
+    })?;
+
     Ok(())
   }
 
   pub fn build(&mut self, settings: &AppSettings) -> Result<()> {
+    self.addons.clear();
     self.airports.clear();
+    self.official_airports.clear();
 
     let addons = addon::get_addons(settings)?;
     for addon in addons {
@@ -75,41 +243,44 @@ impl SceneryCache {
         continue;
       }
 
-      let addon_path = &settings.addons_dir.join(&addon.id);
-      for entry in WalkDir::new(addon_path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().map_or(false, |ext| ext == "bgl"))
-      {
-        let path = entry.path();
-        let bgl_objects = match load_bgl_objects(path) {
-          Ok(b) => b,
-          Err(_) => {
-            continue;
-          }
-        };
-
-        for obj in bgl_objects {
-          match obj {
-            BglObject::Airport(airport) => {
-              self.airports.push(AirportScenery {
-                addon_id: addon.id.to_string(),
-                bgl_path: path.to_string_lossy().to_string(),
-                icao: airport.icao,
-                latitude: airport.latitude,
-                longitude: airport.longitude,
-                altitude: airport.altitude,
-                runway_count: airport.runway_count,
-              });
-            }
-          }
-        }
+      let addon_path = settings.addons_dir.join(&addon.id);
+      let stats = scan_package(&addon.id, &addon_path, &mut self.airports);
+      self.addons.push(stats);
+    }
+
+    if settings.index_official_packages {
+      if let Some(official_dir) = SimManager::new(settings).official_packages_dir() {
+        self.build_official(&official_dir);
       }
     }
 
     Ok(())
   }
 
+  /// Indexes the scenery packages in `official_dir`. Unreadable entries are
+  /// skipped like unreadable community addons, so that they never prevent
+  /// the community airports from being indexed.
+  fn build_official(&mut self, official_dir: &Path) {
+    let Ok(entries) = fs::read_dir(official_dir) else {
+      return;
+    };
+
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let Some(package_id) = path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+        continue;
+      };
+
+      let is_scenery = Manifest::parse(path.join("manifest.json"))
+        .is_ok_and(|manifest| manifest.content_type == "SCENERY");
+      if !is_scenery {
+        continue;
+      }
+
+      scan_package(&package_id, &path, &mut self.official_airports);
+    }
+  }
+
   pub fn all_airports(&self) -> &Vec<AirportScenery> {
     &self.airports
   }
@@ -117,4 +288,305 @@ impl SceneryCache {
   pub fn is_empty(&self) -> bool {
     self.airports.is_empty()
   }
+
+  /// Summarizes the airports of every scenery addon in the cache, sorted by
+  /// addon id. Addons whose BGL files contain no airports are included with
+  /// an airport count of zero.
+  pub fn addon_summaries(&self) -> Vec<AddonScenerySummary> {
+    let mut summaries: BTreeMap<&str, AddonScenerySummary> = BTreeMap::new();
+
+    for stats in &self.addons {
+      let summary = summaries
+        .entry(&stats.addon_id)
+        .or_insert_with(|| AddonScenerySummary::empty(&stats.addon_id));
+      summary.bgl_count = stats.bgl_count;
+      summary.bgl_failures = stats.bgl_failures;
+    }
+
+    for airport in &self.airports {
+      let summary = summaries
+        .entry(&airport.addon_id)
+        .or_insert_with(|| AddonScenerySummary::empty(&airport.addon_id));
+
+      summary.airport_count += 1;
+      if !summary.icaos.contains(&airport.icao) {
+        summary.icaos.push(airport.icao.clone());
+      }
+
+      match summary.bounds.as_mut() {
+        Some(bounds) => bounds.extend(airport.latitude, airport.longitude),
+        None => summary.bounds = Some(BoundingBox::from_point(airport.latitude, airport.longitude)),
+      }
+    }
+
+    summaries
+      .into_values()
+      .map(|mut summary| {
+        summary.icaos.sort();
+        summary
+      })
+      .collect()
+  }
+
+  pub fn official_airports(&self) -> &Vec<AirportScenery> {
+    &self.official_airports
+  }
+
+  /// Lists every official airport that a community addon replaces, matched
+  /// by ICAO code and sorted by addon id. Empty unless official packages
+  /// were indexed.
+  pub fn airport_overrides(&self) -> Vec<AirportOverride> {
+    let mut official: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for airport in &self.official_airports {
+      official
+        .entry(airport.icao.as_str())
+        .or_default()
+        .insert(airport.addon_id.as_str());
+    }
+
+    let mut overrides: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+    for airport in &self.airports {
+      if let Some(packages) = official.get(airport.icao.as_str()) {
+        for package_id in packages {
+          overrides.insert((&airport.addon_id, &airport.icao, package_id));
+        }
+      }
+    }
+
+    overrides
+      .into_iter()
+      .map(|(addon_id, icao, package_id)| AirportOverride {
+        addon_id: addon_id.to_string(),
+        icao: icao.to_string(),
+        package_id: package_id.to_string(),
+        source: PackageSource::of(package_id),
+      })
+      .collect()
+  }
+
+  /// Summarizes the airports provided by a single addon, or `None` if the
+  /// addon was not indexed as scenery.
+  pub fn addon_summary(&self, addon_id: &str) -> Option<AddonScenerySummary> {
+    self
+      .addon_summaries()
+      .into_iter()
+      .find(|summary| summary.addon_id == addon_id)
+  }
+}
+
+fn scan_package(
+  package_id: &str,
+  package_path: &Path,
+  airports: &mut Vec<AirportScenery>,
+) -> SceneryAddonStats {
+  let mut stats = SceneryAddonStats {
+    addon_id: package_id.to_string(),
+    bgl_count: 0,
+    bgl_failures: 0,
+  };
+
+  for entry in WalkDir::new(package_path)
+    .into_iter()
+    .filter_map(|e| e.ok())
+    .filter(|e| e.path().extension().is_some_and(|ext| ext == "bgl"))
+  {
+    stats.bgl_count += 1;
+
+    let path = entry.path();
+    let bgl_objects = match load_bgl_objects(path) {
+      Ok(b) => b,
+      Err(_) => {
+        stats.bgl_failures += 1;
+        continue;
+      }
+    };
+
+    for obj in bgl_objects {
+      match obj {
+        BglObject::Airport(airport) => {
+          airports.push(AirportScenery {
+            addon_id: package_id.to_string(),
+            bgl_path: path.to_string_lossy().to_string(),
+            icao: airport.icao,
+            latitude: airport.latitude,
+            longitude: airport.longitude,
+            altitude: airport.altitude,
+            runway_count: airport.runway_count,
+          });
+        }
+      }
+    }
+  }
+
+  stats
+}
+
+fn read_records<T, B>(lines: &mut Lines<B>, count: usize) -> Result<Vec<T>>
+where
+  T: DeserializeOwned,
+  B: BufRead,
+{
+  let mut records = Vec::with_capacity(count);
+  for i in 0..count {
+    let line = match lines.next() {
+      Some(line) => line?,
+      None => bail!("Scenery cache is truncated ({} of {} records)", i, count),
+    };
+
+    let record = serde_json::from_str(&line)
+      .with_context(|| format!("Invalid scenery cache record: {}", line))?;
+    records.push(record);
+  }
+
+  Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+  use tempfile::tempdir;
+
+  fn airport(icao: &str) -> AirportScenery {
+    airport_at("test-airport", icao, 41.2753, 28.7519)
+  }
+
+  fn airport_at(addon_id: &str, icao: &str, latitude: f64, longitude: f64) -> AirportScenery {
+    AirportScenery {
+      addon_id: addon_id.to_string(),
+      bgl_path: "scenery/test.bgl".to_string(),
+      icao: icao.to_string(),
+      latitude,
+      longitude,
+      altitude: 99.0,
+      runway_count: 5,
+    }
+  }
+
+  #[test]
+  fn test_save_and_load_round_trip() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("scenery.json");
+
+    let mut cache = SceneryCache::new(&path);
+    cache.airports = vec![airport("LTFM"), airport("LTBA")];
+    cache.official_airports = vec![airport_at("asobo-airport-ltfm", "LTFM", 41.27, 28.75)];
+    cache.save().unwrap();
+
+    let loaded = SceneryCache::load(&path).unwrap();
+    let icaos: Vec<_> = loaded
+      .all_airports()
+      .iter()
+      .map(|a| a.icao.as_str())
+      .collect();
+    assert_eq!(icaos, ["LTFM", "LTBA"]);
+    assert_eq!(loaded.official_airports().len(), 1);
+    // No temporary file is left behind.
+    let files: Vec<_> = fs::read_dir(dir.path())
+      .unwrap()
+      .map(|e| e.unwrap().file_name())
+      .collect();
+    assert_eq!(files, ["scenery.json"]);
+  }
+
+  #[test]
+  fn test_load_rejects_other_versions_and_truncated_files() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("scenery.json");
+
+    fs::write(
+      &path,
+      "{\"version\":0,\"addon_count\":0,\"airport_count\":0}\n",
+    )
+    .unwrap();
+    assert!(SceneryCache::load(&path).is_err());
+
+    fs::write(&path, "{\n  \"airports\": []\n}").unwrap();
+    assert!(SceneryCache::load(&path).is_err());
+
+    let record = serde_json::to_string(&airport("LTFM")).unwrap();
+    let data = format!(
+      "{{\"version\":{},\"addon_count\":0,\"airport_count\":2,\"official_airport_count\":0}}\n{}\n",
+      SCENERY_CACHE_VERSION, record
+    );
+    fs::write(&path, data).unwrap();
+    assert!(SceneryCache::load(&path).is_err());
+  }
+
+  #[test]
+  fn test_addon_summaries() {
+    let mut cache = SceneryCache::new("scenery.json");
+    cache.addons = vec![
+      SceneryAddonStats {
+        addon_id: "norway-airports".to_string(),
+        bgl_count: 3,
+        bgl_failures: 1,
+      },
+      SceneryAddonStats {
+        addon_id: "empty-scenery".to_string(),
+        bgl_count: 1,
+        bgl_failures: 0,
+      },
+    ];
+    cache.airports = vec![
+      airport_at("norway-airports", "ENGM", 60.19, 11.10),
+      airport_at("norway-airports", "ENBR", 60.29, 5.21),
+      airport_at("norway-airports", "ENTC", 69.68, 18.91),
+    ];
+
+    let summaries = cache.addon_summaries();
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].addon_id, "empty-scenery");
+    assert_eq!(summaries[0].airport_count, 0);
+    assert!(summaries[0].bounds.is_none());
+
+    let norway = cache.addon_summary("norway-airports").unwrap();
+    assert_eq!(norway.airport_count, 3);
+    assert_eq!(norway.icaos, ["ENBR", "ENGM", "ENTC"]);
+    assert_eq!(norway.bgl_count, 3);
+    assert_eq!(norway.bgl_failures, 1);
+    assert_eq!(
+      norway.bounds,
+      Some(BoundingBox {
+        min_latitude: 60.19,
+        min_longitude: 5.21,
+        max_latitude: 69.68,
+        max_longitude: 18.91,
+      })
+    );
+  }
+
+  #[test]
+  fn test_airport_overrides() {
+    let mut cache = SceneryCache::new("scenery.json");
+    cache.airports = vec![
+      airport_at("freeware-engm", "ENGM", 60.19, 11.10),
+      airport_at("freeware-engm", "ENBR", 60.29, 5.21),
+    ];
+    cache.official_airports = vec![
+      airport_at("asobo-airport-engm", "ENGM", 60.19, 11.10),
+      airport_at("orbx-airport-engm", "ENGM", 60.19, 11.10),
+      airport_at("asobo-airport-entc", "ENTC", 69.68, 18.91),
+    ];
+
+    let overrides = cache.airport_overrides();
+    assert_eq!(
+      overrides,
+      [
+        AirportOverride {
+          addon_id: "freeware-engm".to_string(),
+          icao: "ENGM".to_string(),
+          package_id: "asobo-airport-engm".to_string(),
+          source: PackageSource::Stock,
+        },
+        AirportOverride {
+          addon_id: "freeware-engm".to_string(),
+          icao: "ENGM".to_string(),
+          package_id: "orbx-airport-engm".to_string(),
+          source: PackageSource::Marketplace,
+        },
+      ]
+    );
+  }
 }
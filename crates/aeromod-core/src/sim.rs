@@ -1,8 +1,15 @@
+use std::env;
+use std::ffi::OsStr;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use aeromod_settings::AppSettings;
 use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::community::same_path;
 
 pub struct SimManager<'a> {
   settings: &'a AppSettings,
@@ -37,12 +44,396 @@ impl<'a> SimManager<'a> {
     Ok(())
   }
 
+  /// Returns the folder holding the sim's official and marketplace packages,
+  /// which sits next to the community folder as `Official/OneStore` for
+  /// Microsoft Store installs or `Official/Steam` for Steam installs.
+  pub fn official_packages_dir(&self) -> Option<PathBuf> {
+    let official_dir = self.settings.community_dir.parent()?.join("Official");
+
+    ["OneStore", "Steam"]
+      .iter()
+      .map(|name| official_dir.join(name))
+      .find(|path| path.is_dir())
+  }
+
+  /// Returns the path of `Content.xml`, which holds the activation state of
+  /// official and marketplace packages.
+  pub fn content_xml_path(&self) -> Option<PathBuf> {
+    self.sim_path().map(|path| path.join("Content.xml"))
+  }
+
+  /// Returns the directory holding `UserCfg.opt`, the rolling cache and
+  /// `Content.xml`. Uses the detected install whose packages directory holds
+  /// the community folder, and falls back to the directory two levels above
+  /// the community folder, where a default Store install keeps them.
   fn sim_path(&self) -> Option<PathBuf> {
-    self
-      .settings
-      .community_dir
-      .parent()?
-      .parent()
-      .map(PathBuf::from)
+    let packages_dir = self.settings.community_dir.parent()?;
+    if let Some(install) = known_installs()
+      .into_iter()
+      .find(|install| same_path(&install.packages_dir, packages_dir))
+    {
+      return Some(install.config_dir);
+    }
+
+    packages_dir.parent().map(PathBuf::from)
+  }
+}
+
+/// Whether a sim process is running, natively or under Proton.
+pub fn is_sim_running() -> bool {
+  let mut system = System::new();
+  system.refresh_processes_specifics(ProcessesToUpdate::All, true, ProcessRefreshKind::nothing());
+
+  system
+    .processes()
+    .values()
+    .any(|process| is_sim_process(process.name()))
+}
+
+/// Matches `FlightSimulator.exe` and `FlightSimulator2024.exe`. Linux cuts
+/// process names to 15 characters, which leaves `FlightSimulator`.
+fn is_sim_process(name: &OsStr) -> bool {
+  name
+    .to_string_lossy()
+    .to_lowercase()
+    .starts_with("flightsimulator")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SimVersion {
+  #[serde(rename = "2020")]
+  Msfs2020,
+  #[serde(rename = "2024")]
+  Msfs2024,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+  Store,
+  Steam,
+}
+
+/// A sim installation found through its `UserCfg.opt`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SimInstall {
+  pub version: SimVersion,
+  pub distribution: Distribution,
+  /// Directory holding `UserCfg.opt`.
+  pub config_dir: PathBuf,
+  /// The `InstalledPackagesPath` from `UserCfg.opt`.
+  pub packages_dir: PathBuf,
+  pub community_dir: PathBuf,
+  /// Found in a Steam Proton prefix.
+  pub proton: bool,
+}
+
+/// A directory that may hold `UserCfg.opt`, and the Wine prefix it lives in
+/// when the sim runs under Proton.
+struct Candidate {
+  version: SimVersion,
+  distribution: Distribution,
+  config_dir: PathBuf,
+  prefix: Option<PathBuf>,
+}
+
+const STORE_PACKAGES: [(SimVersion, &str); 2] = [
+  (
+    SimVersion::Msfs2020,
+    "Microsoft.FlightSimulator_8wekyb3d8bbwe",
+  ),
+  (SimVersion::Msfs2024, "Microsoft.Limitless_8wekyb3d8bbwe"),
+];
+
+const STEAM_APPS: [(SimVersion, &str, &str); 2] = [
+  (
+    SimVersion::Msfs2020,
+    "1250410",
+    "Microsoft Flight Simulator",
+  ),
+  (
+    SimVersion::Msfs2024,
+    "2537590",
+    "Microsoft Flight Simulator 2024",
+  ),
+];
+
+/// Installs found by the last [`detect_installs`].
+static INSTALLS: Mutex<Option<Vec<SimInstall>>> = Mutex::new(None);
+
+/// The installs found by the last detection, detecting them on first use.
+fn known_installs() -> Vec<SimInstall> {
+  let cached = INSTALLS.lock().unwrap_or_else(|e| e.into_inner()).clone();
+  cached.unwrap_or_else(detect_installs)
+}
+
+/// Finds the sim installs of the current user, for both MSFS 2020 and 2024
+/// from the Microsoft Store and Steam, including Steam Proton prefixes on
+/// Linux. The result is kept for locating the sim's files later on.
+pub fn detect_installs() -> Vec<SimInstall> {
+  let mut candidates = Vec::new();
+
+  if let Some(local_app_data) = env::var_os("LOCALAPPDATA") {
+    let packages = Path::new(&local_app_data).join("Packages");
+    for (version, package) in STORE_PACKAGES {
+      candidates.push(Candidate {
+        version,
+        distribution: Distribution::Store,
+        config_dir: packages.join(package).join("LocalCache"),
+        prefix: None,
+      });
+    }
+  }
+
+  if let Some(app_data) = env::var_os("APPDATA") {
+    for (version, _, dir_name) in STEAM_APPS {
+      candidates.push(Candidate {
+        version,
+        distribution: Distribution::Steam,
+        config_dir: Path::new(&app_data).join(dir_name),
+        prefix: None,
+      });
+    }
+  }
+
+  if cfg!(target_os = "linux") {
+    if let Some(home) = env::var_os("HOME") {
+      candidates.extend(proton_candidates(Path::new(&home)));
+    }
+  }
+
+  let installs = installs_from(candidates);
+  *INSTALLS.lock().unwrap_or_else(|e| e.into_inner()) = Some(installs.clone());
+  installs
+}
+
+fn installs_from(candidates: Vec<Candidate>) -> Vec<SimInstall> {
+  let mut installs: Vec<SimInstall> = Vec::new();
+
+  for candidate in candidates {
+    let Ok(text) = fs::read_to_string(candidate.config_dir.join("UserCfg.opt")) else {
+      continue;
+    };
+    let Some(value) = installed_packages_path(&text) else {
+      continue;
+    };
+
+    let packages_dir = match &candidate.prefix {
+      Some(prefix) => wine_to_unix_path(prefix, value),
+      None => PathBuf::from(value),
+    };
+    if installs
+      .iter()
+      .any(|install| same_path(&install.packages_dir, &packages_dir))
+    {
+      continue;
+    }
+
+    installs.push(SimInstall {
+      version: candidate.version,
+      distribution: candidate.distribution,
+      config_dir: candidate.config_dir,
+      community_dir: packages_dir.join("Community"),
+      packages_dir,
+      proton: candidate.prefix.is_some(),
+    });
+  }
+
+  installs
+}
+
+/// Reads the `InstalledPackagesPath "..."` line of `UserCfg.opt`.
+fn installed_packages_path(text: &str) -> Option<&str> {
+  text.lines().find_map(|line| {
+    let value = line.trim().strip_prefix("InstalledPackagesPath")?;
+    if !value.starts_with(char::is_whitespace) {
+      return None;
+    }
+
+    let value = value.trim();
+    let value = value.trim_matches('"');
+    (!value.is_empty()).then_some(value)
+  })
+}
+
+/// Lists the `UserCfg.opt` locations inside the Proton prefixes of every
+/// Steam library of the user.
+fn proton_candidates(home: &Path) -> Vec<Candidate> {
+  let steam_roots = [
+    home.join(".steam").join("steam"),
+    home.join(".local").join("share").join("Steam"),
+    home
+      .join(".var")
+      .join("app")
+      .join("com.valvesoftware.Steam")
+      .join(".local")
+      .join("share")
+      .join("Steam"),
+  ];
+
+  let mut libraries: Vec<PathBuf> = Vec::new();
+  for root in steam_roots.iter().filter(|root| root.is_dir()) {
+    let folders = fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf"))
+      .map(|text| library_folders(&text))
+      .unwrap_or_default();
+
+    for library in std::iter::once(root.clone()).chain(folders) {
+      if !libraries.iter().any(|l| same_path(l, &library)) {
+        libraries.push(library);
+      }
+    }
+  }
+
+  let mut candidates = Vec::new();
+  for library in libraries {
+    for (version, app_id, dir_name) in STEAM_APPS {
+      let prefix = library
+        .join("steamapps")
+        .join("compatdata")
+        .join(app_id)
+        .join("pfx");
+      let roaming = prefix
+        .join("drive_c")
+        .join("users")
+        .join("steamuser")
+        .join("AppData")
+        .join("Roaming");
+
+      candidates.push(Candidate {
+        version,
+        distribution: Distribution::Steam,
+        config_dir: roaming.join(dir_name),
+        prefix: Some(prefix),
+      });
+    }
+  }
+
+  candidates
+}
+
+/// Reads the library paths of Steam's `libraryfolders.vdf`.
+fn library_folders(text: &str) -> Vec<PathBuf> {
+  text
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.split('"').filter(|part| !part.trim().is_empty());
+      if parts.next()? != "path" {
+        return None;
+      }
+      parts
+        .next()
+        .map(|path| PathBuf::from(path.replace("\\\\", "\\")))
+    })
+    .collect()
+}
+
+/// Maps a Windows path from inside a Wine prefix to the host. `Z:` is the
+/// host root, other drives are resolved through the prefix's `dosdevices`.
+fn wine_to_unix_path(prefix: &Path, path: &str) -> PathBuf {
+  let path = path.replace('\\', "/");
+  let Some((drive, rest)) = path.split_once(':').filter(|(drive, _)| drive.len() == 1) else {
+    return PathBuf::from(path);
+  };
+
+  let rest = rest.trim_start_matches('/');
+  if drive.eq_ignore_ascii_case("z") {
+    Path::new("/").join(rest)
+  } else {
+    prefix
+      .join("dosdevices")
+      .join(format!("{}:", drive.to_lowercase()))
+      .join(rest)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tempfile::tempdir;
+
+  #[test]
+  fn test_installs_from_user_cfg() {
+    let dir = tempdir().unwrap();
+
+    let store = dir.path().join("LocalCache");
+    fs::create_dir_all(&store).unwrap();
+    fs::write(
+      store.join("UserCfg.opt"),
+      "Version 61\nInstalledPackagesPath \"D:\\MSFS\\Packages\"\n",
+    )
+    .unwrap();
+
+    let prefix = dir.path().join("pfx");
+    let roaming = prefix.join("Roaming");
+    fs::create_dir_all(&roaming).unwrap();
+    fs::write(
+      roaming.join("UserCfg.opt"),
+      "InstalledPackagesPath \"C:\\Games\\MSFS 2024\"\n",
+    )
+    .unwrap();
+
+    let installs = installs_from(vec![
+      Candidate {
+        version: SimVersion::Msfs2020,
+        distribution: Distribution::Store,
+        config_dir: store.clone(),
+        prefix: None,
+      },
+      Candidate {
+        version: SimVersion::Msfs2024,
+        distribution: Distribution::Steam,
+        config_dir: roaming,
+        prefix: Some(prefix.clone()),
+      },
+      Candidate {
+        version: SimVersion::Msfs2024,
+        distribution: Distribution::Store,
+        config_dir: dir.path().join("missing"),
+        prefix: None,
+      },
+    ]);
+
+    assert_eq!(installs.len(), 2);
+    assert_eq!(installs[0].config_dir, store);
+    assert_eq!(
+      installs[0].packages_dir,
+      PathBuf::from("D:\\MSFS\\Packages")
+    );
+    assert!(!installs[0].proton);
+    assert_eq!(
+      installs[1].community_dir,
+      prefix.join("dosdevices/c:/Games/MSFS 2024/Community")
+    );
+    assert!(installs[1].proton);
+
+    assert_eq!(
+      installed_packages_path(
+        "InstalledPackagesPathBackup \"E:\\Old\"\nInstalledPackagesPath \"D:\\MSFS\""
+      ),
+      Some("D:\\MSFS")
+    );
+
+    assert_eq!(
+      wine_to_unix_path(&prefix, "Z:\\home\\user\\MSFS"),
+      PathBuf::from("/home/user/MSFS")
+    );
+    assert_eq!(
+      library_folders(
+        "\"libraryfolders\"\n{\n\t\"0\"\n\t{\n\t\t\"path\"\t\t\"/mnt/games/SteamLibrary\"\n"
+      ),
+      [PathBuf::from("/mnt/games/SteamLibrary")]
+    );
+  }
+
+  #[test]
+  fn test_is_sim_process() {
+    for name in [
+      "FlightSimulator.exe",
+      "FlightSimulator2024.exe",
+      "FlightSimulator",
+    ] {
+      assert!(is_sim_process(OsStr::new(name)));
+    }
+    assert!(!is_sim_process(OsStr::new("aeromod")));
   }
 }
@@ -0,0 +1,53 @@
+use std::fs;
+use std::path::Path;
+
+use aeromod_settings::{ActivationMode, AppSettings};
+use tempfile::{TempDir, tempdir};
+
+/// Settings with an empty addons directory and community folder inside a
+/// temporary directory, which must be kept alive for the test.
+pub fn settings() -> (TempDir, AppSettings) {
+  let dir = tempdir().unwrap();
+  let settings = AppSettings {
+    addons_dir: dir.path().join("addons"),
+    community_dir: dir.path().join("Community"),
+    auto_enable: true,
+    auto_clear_scenery_indexes: false,
+    index_official_packages: false,
+    retain_archives: false,
+    activation_mode: ActivationMode::Symlink,
+    config_dir: Some(dir.path().join("config")),
+  };
+  fs::create_dir_all(&settings.addons_dir).unwrap();
+  fs::create_dir_all(&settings.community_dir).unwrap();
+  (dir, settings)
+}
+
+/// Writes a scenery package with version 1.0.0 to `dir`.
+pub fn write_package(dir: &Path, title: &str) {
+  write_versioned_package(dir, title, "1.0.0", &[]);
+}
+
+/// Writes a scenery package to `dir` that depends on the given
+/// `(name, version)` pairs.
+pub fn write_versioned_package(dir: &Path, title: &str, version: &str, deps: &[(&str, &str)]) {
+  let dependencies: Vec<_> = deps
+    .iter()
+    .map(|(name, version)| serde_json::json!({ "name": name, "package_version": version }))
+    .collect();
+  write_manifest(
+    dir,
+    serde_json::json!({
+      "content_type": "SCENERY",
+      "title": title,
+      "package_version": version,
+      "dependencies": dependencies,
+    }),
+  );
+}
+
+/// Creates `dir` with the given `manifest.json`.
+pub fn write_manifest(dir: &Path, manifest: serde_json::Value) {
+  fs::create_dir_all(dir).unwrap();
+  fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+}
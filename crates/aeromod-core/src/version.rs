@@ -0,0 +1,190 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+const PRERELEASE_TAGS: &[&str] = &["alpha", "beta", "rc", "pre", "preview"];
+
+/// A package version as found in MSFS manifests.
+///
+/// Parsing never fails: the leading dotted numbers (`1.2.3`, `1.2.3.4`) are
+/// compared numerically and anything after them (`b` in `v1.0b`) is kept as a
+/// suffix. Trailing zero components are insignificant, so `1.2` equals
+/// `1.2.0`. Pre-release suffixes such as `-beta` or `rc1` sort before the
+/// plain release, any other suffix sorts after it.
+#[derive(Clone, Debug, Default)]
+pub struct Version {
+  raw: String,
+  parts: Vec<u64>,
+  suffix: String,
+}
+
+impl Version {
+  pub fn parse(value: &str) -> Self {
+    let raw = value.trim().to_string();
+    let rest = raw
+      .strip_prefix(['v', 'V'])
+      .filter(|r| r.starts_with(|c: char| c.is_ascii_digit()))
+      .unwrap_or(&raw);
+
+    let mut parts = Vec::new();
+    let mut remaining = rest;
+
+    let suffix = loop {
+      let digits = remaining
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(remaining.len());
+      if digits == 0 {
+        break remaining;
+      }
+
+      match remaining[..digits].parse() {
+        Ok(part) => parts.push(part),
+        Err(_) => break remaining,
+      }
+
+      remaining = &remaining[digits..];
+      match remaining.strip_prefix('.') {
+        Some(next) if next.starts_with(|c: char| c.is_ascii_digit()) => remaining = next,
+        _ => break remaining,
+      }
+    };
+
+    while parts.last() == Some(&0) {
+      parts.pop();
+    }
+
+    Self {
+      suffix: suffix.to_lowercase(),
+      raw,
+      parts,
+    }
+  }
+
+  /// Whether the version starts with at least one numeric component.
+  pub fn is_numeric(&self) -> bool {
+    self
+      .raw
+      .trim_start_matches(['v', 'V'])
+      .starts_with(|c: char| c.is_ascii_digit())
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.raw
+  }
+
+  fn is_prerelease(&self) -> bool {
+    let suffix = self.suffix.trim_start_matches(['-', '_', '.', '+']);
+    self.suffix.starts_with('-') || PRERELEASE_TAGS.iter().any(|tag| suffix.starts_with(tag))
+  }
+}
+
+impl Ord for Version {
+  fn cmp(&self, other: &Self) -> Ordering {
+    let len = self.parts.len().max(other.parts.len());
+    let parts = (0..len).map(|i| {
+      let a = self.parts.get(i).copied().unwrap_or(0);
+      let b = other.parts.get(i).copied().unwrap_or(0);
+      a.cmp(&b)
+    });
+
+    if let Some(ordering) = parts.into_iter().find(|o| o.is_ne()) {
+      return ordering;
+    }
+
+    match (self.suffix.is_empty(), other.suffix.is_empty()) {
+      (true, true) => Ordering::Equal,
+      (true, false) if other.is_prerelease() => Ordering::Greater,
+      (true, false) => Ordering::Less,
+      (false, true) if self.is_prerelease() => Ordering::Less,
+      (false, true) => Ordering::Greater,
+      (false, false) => match (self.is_prerelease(), other.is_prerelease()) {
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        _ => self.suffix.cmp(&other.suffix),
+      },
+    }
+  }
+}
+
+impl PartialOrd for Version {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq for Version {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.raw)
+  }
+}
+
+impl FromStr for Version {
+  type Err = std::convert::Infallible;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(Self::parse(s))
+  }
+}
+
+impl From<&str> for Version {
+  fn from(value: &str) -> Self {
+    Self::parse(value)
+  }
+}
+
+impl Serialize for Version {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.raw)
+  }
+}
+
+impl<'de> Deserialize<'de> for Version {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(Self::parse(&value))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn v(value: &str) -> Version {
+    Version::parse(value)
+  }
+
+  #[test]
+  fn test_parse_msfs_versions() {
+    assert_eq!(v("1.2.3").parts, [1, 2, 3]);
+    assert_eq!(v("1.2.3.4").parts, [1, 2, 3, 4]);
+    assert_eq!(v("v1.0b").parts, [1]);
+    assert_eq!(v("v1.0b").suffix, "b");
+    assert_eq!(v("1.0.0-beta.2").suffix, "-beta.2");
+    assert_eq!(v(" 0.1.10 ").to_string(), "0.1.10");
+    assert!(!v("latest").is_numeric());
+  }
+
+  #[test]
+  fn test_version_ordering() {
+    assert!(v("1.10.0") > v("1.9.9"));
+    assert!(v("1.2.3.4") > v("1.2.3"));
+    assert_eq!(v("1.2"), v("1.2.0.0"));
+    assert_eq!(v("v1.0"), v("1.0"));
+    assert!(v("1.0b") > v("1.0a"));
+    assert!(v("1.0b") > v("1.0"));
+    assert!(v("1.0.0-beta") < v("1.0.0"));
+    assert!(v("1.0rc1") < v("1.0"));
+    assert!(v("1.0rc1") < v("1.0a"));
+    assert!(v("garbage") < v("0.0.1"));
+  }
+}
@@ -2,13 +2,22 @@ use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::{Read, Seek};
-use std::path::Path;
+use std::path::{Component, Path};
 
 use anyhow::{Result, anyhow};
 use unrar::Archive;
 use zip::ZipArchive;
 
 pub fn extract_archive(archive_path: &Path, target_dir: &Path) -> Result<()> {
+  extract_archive_entries(archive_path, target_dir, |_| true)
+}
+
+/// Extracts only the files of the archive for which `select` returns true,
+/// given their path inside the archive.
+pub fn extract_archive_entries<F>(archive_path: &Path, target_dir: &Path, select: F) -> Result<()>
+where
+  F: Fn(&Path) -> bool,
+{
   fs::create_dir_all(target_dir)?;
 
   let file = File::open(archive_path)?;
@@ -18,13 +27,23 @@ pub fn extract_archive(archive_path: &Path, target_dir: &Path) -> Result<()> {
     .unwrap_or("");
 
   match archive_type {
-    "zip" => extract_zip_archive(file, target_dir),
-    "rar" => extract_rar_archive(archive_path, target_dir),
+    "zip" => extract_zip_archive(file, target_dir, select),
+    "rar" => extract_rar_archive(archive_path, target_dir, select),
     _ => Err(anyhow!("Unsupported archive type: '{}'", archive_type)),
   }
 }
 
-fn extract_zip_archive<R: Read + Seek>(reader: R, target_dir: &Path) -> Result<()> {
+/// Whether `path` is a plain relative path that stays inside the directory
+/// it is joined to.
+pub fn is_enclosed(path: &Path) -> bool {
+  path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn extract_zip_archive<R, F>(reader: R, target_dir: &Path, select: F) -> Result<()>
+where
+  R: Read + Seek,
+  F: Fn(&Path) -> bool,
+{
   let mut archive = ZipArchive::new(reader)?;
 
   for i in 0..archive.len() {
@@ -34,6 +53,10 @@ fn extract_zip_archive<R: Read + Seek>(reader: R, target_dir: &Path) -> Result<(
       None => continue,
     };
 
+    if !select(&file_path) {
+      continue;
+    }
+
     let target_path = target_dir.join(file_path);
 
     if file.is_dir() {
@@ -63,12 +86,15 @@ fn extract_zip_archive<R: Read + Seek>(reader: R, target_dir: &Path) -> Result<(
   Ok(())
 }
 
-fn extract_rar_archive(archive_path: &Path, target_dir: &Path) -> Result<()> {
+fn extract_rar_archive<F>(archive_path: &Path, target_dir: &Path, select: F) -> Result<()>
+where
+  F: Fn(&Path) -> bool,
+{
   let mut archive = Archive::new(archive_path).open_for_processing()?;
   while let Some(header) = archive.read_header()? {
     let entry = header.entry();
 
-    archive = if header.entry().is_file() {
+    archive = if entry.is_file() && is_enclosed(&entry.filename) && select(&entry.filename) {
       let target_path = target_dir.join(&entry.filename);
       header.extract_to(target_path)?
     } else {
@@ -1,9 +1,15 @@
 use std::fs;
+use std::fs::File;
 use std::io;
+use std::io::BufWriter;
 use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 mod extract;
-pub use self::extract::extract_archive;
+pub use self::extract::{extract_archive, extract_archive_entries, is_enclosed};
+
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 pub fn get_directory_size<P: AsRef<Path>>(path: P) -> io::Result<u64> {
   let mut size = 0;
@@ -43,6 +49,40 @@ pub fn copy_dir_all<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> io::Resul
   Ok(())
 }
 
+/// Writes a file by streaming into a sibling temporary file and renaming it
+/// over `path`, so readers never observe a partially written file. Each call
+/// uses its own temporary file so that concurrent writers do not clobber
+/// each other; the last rename wins.
+pub fn write_atomic<P, F>(path: P, write: F) -> io::Result<()>
+where
+  P: AsRef<Path>,
+  F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+  let path = path.as_ref();
+  let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+  tmp_name.push(format!(
+    ".{}.{}.tmp",
+    process::id(),
+    TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  let tmp_path = path.with_file_name(tmp_name);
+
+  let result = File::create(&tmp_path).and_then(|file| {
+    let mut writer = BufWriter::new(file);
+    write(&mut writer)?;
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()
+  });
+
+  match result.and_then(|_| fs::rename(&tmp_path, path)) {
+    Ok(()) => Ok(()),
+    Err(e) => {
+      let _ = fs::remove_file(&tmp_path);
+      Err(e)
+    }
+  }
+}
+
 #[cfg(unix)]
 pub fn symlink_dir<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> io::Result<()> {
   std::os::unix::fs::symlink(from, to)?;
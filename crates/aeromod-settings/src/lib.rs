@@ -9,6 +9,20 @@ fn yes() -> bool {
   true
 }
 
+/// How an enabled addon is made visible in the community folder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivationMode {
+  /// A symlink on Unix, a junction on Windows.
+  #[default]
+  Symlink,
+  /// A directory tree whose files are hard links to the addon files. The
+  /// community folder must be on the same volume as the addons directory.
+  Hardlink,
+  /// A full copy of the addon.
+  Copy,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppSettings {
   pub addons_dir: PathBuf,
@@ -17,6 +31,16 @@ pub struct AppSettings {
   pub auto_enable: bool,
   #[serde(default)]
   pub auto_clear_scenery_indexes: bool,
+  #[serde(default)]
+  pub index_official_packages: bool,
+  #[serde(default)]
+  pub retain_archives: bool,
+  #[serde(default)]
+  pub activation_mode: ActivationMode,
+  /// AeroMod's config directory, holding the settings file and other user
+  /// data. Set by the application after loading the settings, not stored.
+  #[serde(skip)]
+  pub config_dir: Option<PathBuf>,
 }
 
 impl AppSettings {
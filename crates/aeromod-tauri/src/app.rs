@@ -1,12 +1,17 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-use aeromod_core::scenery::SceneryCache;
-use aeromod_core::{addon, preset, sim};
-use aeromod_settings::AppSettings;
-use serde::Serialize;
+use aeromod_core::flightplan::FlightPlan;
+use aeromod_core::scenery::{AddonScenerySummary, AirportOverride, SceneryCache};
+use aeromod_core::{
+  addon, archive, community, content, dependency, export, layout, metadata, preset, query,
+  relocate, sim,
+};
+use aeromod_settings::{ActivationMode, AppSettings};
+use serde::{Deserialize, Serialize};
 use sysinfo::System;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_opener::OpenerExt;
@@ -44,19 +49,63 @@ pub fn show_about(app_handle: AppHandle) {
 }
 
 #[tauri::command(async)]
-pub fn get_addon(state: State<'_, Mutex<AppSettings>>, id: &str) -> Result<addon::Addon, String> {
+pub fn get_addon(
+  state: State<'_, Mutex<AppSettings>>,
+  scenery_cache: State<'_, SceneryCacheState>,
+  id: &str,
+  with_scenery: Option<bool>,
+) -> Result<addon::Addon, String> {
   let state = state.lock().unwrap().clone();
-  let addons = addon::parse_addon(&state, id).map_err(|e| e.to_string())?;
+  let mut addon = addon::parse_addon(&state, id).map_err(|e| e.to_string())?;
 
-  Ok(addons)
+  if with_scenery.unwrap_or(false) {
+    let sc = scenery_cache.lock().map_err(|e| e.to_string())?;
+    addon.scenery = sc.addon_summary(id);
+  }
+
+  Ok(addon)
 }
 
 #[tauri::command(async)]
 pub fn get_addons(state: State<'_, Mutex<AppSettings>>) -> Result<Vec<addon::Addon>, String> {
+  let state = state.lock().unwrap().clone();
+  addon::get_addons(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn scan_addons(state: State<'_, Mutex<AppSettings>>) -> Result<addon::AddonLibrary, String> {
+  let state = state.lock().unwrap().clone();
+  addon::scan_addons(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn query_addons(
+  state: State<'_, Mutex<AppSettings>>,
+  query: query::AddonQuery,
+) -> Result<query::AddonPage, String> {
   let state = state.lock().unwrap().clone();
   let addons = addon::get_addons(&state).map_err(|e| e.to_string())?;
 
-  Ok(addons)
+  Ok(query.run(addons))
+}
+
+#[tauri::command(async)]
+pub fn update_addon_metadata(
+  state: State<'_, Mutex<AppSettings>>,
+  id: &str,
+  metadata: metadata::AddonMetadata,
+) -> Result<(), String> {
+  let state = state.lock().unwrap().clone();
+  metadata::MetadataStore::update(&state, |store| store.set(id, metadata))
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn list_addon_tags(state: State<'_, Mutex<AppSettings>>) -> Result<Vec<String>, String> {
+  let state = state.lock().unwrap().clone();
+  let store = metadata::MetadataStore::open(&state).map_err(|e| e.to_string())?;
+
+  Ok(store.tags().into_iter().collect())
 }
 
 #[derive(Serialize)]
@@ -85,27 +134,71 @@ pub fn install_addon(app_handle: AppHandle) -> Result<InstallResult, String> {
   };
 
   let mut results = Vec::new();
+  let mut previews = Vec::new();
 
   for addon in addons {
-    let file_display = addon
-      .file_name()
-      .and_then(|n| n.to_str())
-      .unwrap_or("<unknown>")
-      .to_string();
-
-    match addon::install_addon(&settings, &addon) {
-      Ok(id) => {
-        if settings.auto_enable {
-          if let Err(e) = addon::enable_addon(&settings, &id) {
-            results.push(AddonInstallResult::Failure {
-              file: file_display,
+    let file_display = display_file_name(&addon);
+    match addon::preview_install(&settings, &addon) {
+      Ok(preview) => previews.push((addon, preview)),
+      Err(e) => results.push(AddonInstallResult::Failure {
+        file: file_display,
+        error: e.to_string(),
+      }),
+    }
+  }
+
+  if !previews.is_empty() {
+    let confirmed = app_handle
+      .dialog()
+      .message(install_summary(&previews))
+      .kind(MessageDialogKind::Info)
+      .title("Install Addons")
+      .buttons(MessageDialogButtons::OkCancelCustom(
+        "Install".to_string(),
+        "Cancel".to_string(),
+      ))
+      .blocking_show();
+
+    if !confirmed {
+      return Ok(InstallResult { results: vec![] });
+    }
+  }
+
+  // The user has confirmed the summary, which lists updates and downgrades.
+  let options = addon::InstallOptions {
+    update_existing: true,
+    allow_downgrade: true,
+    installer_version: Some(app_handle.package_info().version.to_string()),
+  };
+
+  for (addon, preview) in previews {
+    let file_display = display_file_name(&addon);
+
+    match addon::install_addon_with(&settings, &addon, &options) {
+      Ok(packages) => {
+        for package in packages {
+          // Updates keep the enabled state of the installed addon.
+          let is_new = preview
+            .packages
+            .iter()
+            .any(|p| p.id == package.id && p.status == addon::PackagePreviewStatus::New);
+
+          let result = package.result.and_then(|_| {
+            if settings.auto_enable && is_new {
+              addon::enable_addon(&settings, &package.id)
+            } else {
+              Ok(())
+            }
+          });
+
+          results.push(match result {
+            Ok(()) => AddonInstallResult::Success { id: package.id },
+            Err(e) => AddonInstallResult::Failure {
+              file: format!("{} ({})", file_display, package.id),
               error: e.to_string(),
-            });
-            continue;
-          }
+            },
+          });
         }
-
-        results.push(AddonInstallResult::Success { id });
       }
       Err(e) => {
         results.push(AddonInstallResult::Failure {
@@ -124,15 +217,159 @@ pub fn install_addon(app_handle: AppHandle) -> Result<InstallResult, String> {
 }
 
 #[tauri::command(async)]
-pub fn enable_addon(state: State<'_, Mutex<AppSettings>>, id: &str) -> Result<(), String> {
+pub fn preview_install(
+  state: State<'_, Mutex<AppSettings>>,
+  path: PathBuf,
+) -> Result<addon::InstallPreview, String> {
+  let state = state.lock().unwrap().clone();
+  addon::preview_install(&state, &path).map_err(|e| e.to_string())
+}
+
+fn display_file_name(path: &Path) -> String {
+  path
+    .file_name()
+    .and_then(|n| n.to_str())
+    .unwrap_or("<unknown>")
+    .to_string()
+}
+
+fn install_summary(previews: &[(PathBuf, addon::InstallPreview)]) -> String {
+  let mut lines = Vec::new();
+
+  for (path, preview) in previews {
+    lines.push(format!(
+      "{} ({})",
+      display_file_name(path),
+      format_size(preview.total_size)
+    ));
+
+    for package in &preview.packages {
+      let status = match (&package.status, &package.installed_version) {
+        (addon::PackagePreviewStatus::New, _) => String::from("new"),
+        (addon::PackagePreviewStatus::Upgrade, Some(v)) => format!("update from {}", v),
+        (addon::PackagePreviewStatus::Downgrade, Some(v)) => format!("older than installed {}", v),
+        (addon::PackagePreviewStatus::Reinstall, _) => String::from("already installed"),
+        _ => String::from("conflicts with an existing folder"),
+      };
+
+      lines.push(format!(
+        "  {} {} by {} [{}] - {}",
+        package.name, package.version, package.creator, package.content_type, status
+      ));
+
+      for issue in &package.dependency_issues {
+        lines.push(format!(
+          "    requires {} {}",
+          issue.dependency, issue.required_version
+        ));
+      }
+    }
+  }
+
+  lines.join("\n")
+}
+
+fn format_size(bytes: u64) -> String {
+  const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+  let mut size = bytes as f64;
+  let mut unit = 0;
+  while size >= 1024.0 && unit < UNITS.len() - 1 {
+    size /= 1024.0;
+    unit += 1;
+  }
+
+  format!("{:.1} {}", size, UNITS[unit])
+}
+
+#[tauri::command(async)]
+pub fn enable_addon(
+  state: State<'_, Mutex<AppSettings>>,
+  id: &str,
+  with_dependencies: Option<bool>,
+) -> Result<Vec<String>, String> {
   let state = state.lock().unwrap().clone();
-  addon::enable_addon(&state, id).map_err(|e| e.to_string())?;
+  let enabled_dependencies = if with_dependencies.unwrap_or(false) {
+    dependency::enable_addon_with_dependencies(&state, id).map_err(|e| e.to_string())?
+  } else {
+    addon::enable_addon(&state, id).map_err(|e| e.to_string())?;
+    Vec::new()
+  };
 
   if state.auto_clear_scenery_indexes {
     let _ = sim::SimManager::new(&state).clear_scenery_indexes();
   }
 
-  Ok(())
+  Ok(enabled_dependencies)
+}
+
+#[tauri::command(async)]
+pub fn get_addon_dependencies(
+  state: State<'_, Mutex<AppSettings>>,
+  id: &str,
+) -> Result<Vec<dependency::DependencyIssue>, String> {
+  let state = state.lock().unwrap().clone();
+  dependency::check_addon_dependencies(&state, id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn check_dependencies(
+  state: State<'_, Mutex<AppSettings>>,
+) -> Result<Vec<dependency::DependencyIssue>, String> {
+  let state = state.lock().unwrap().clone();
+  let index = dependency::PackageIndex::load(&state).map_err(|e| e.to_string())?;
+
+  Ok(index.check_enabled())
+}
+
+#[tauri::command(async)]
+pub fn list_unmanaged_packages(
+  state: State<'_, Mutex<AppSettings>>,
+) -> Result<Vec<community::UnmanagedPackage>, String> {
+  let state = state.lock().unwrap().clone();
+  community::find_unmanaged(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn import_unmanaged_package(
+  state: State<'_, Mutex<AppSettings>>,
+  id: &str,
+) -> Result<(), String> {
+  let state = state.lock().unwrap().clone();
+  community::import_unmanaged(&state, id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn list_official_packages(
+  state: State<'_, Mutex<AppSettings>>,
+) -> Result<Vec<content::OfficialPackage>, String> {
+  let state = state.lock().unwrap().clone();
+  content::list_official_packages(&state).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn set_official_packages_active(
+  state: State<'_, Mutex<AppSettings>>,
+  packages: BTreeMap<String, bool>,
+) -> Result<(), String> {
+  let state = state.lock().unwrap().clone();
+  content::set_official_packages_active(&state, &packages).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn reconcile_links(
+  state: State<'_, Mutex<AppSettings>>,
+  dry_run: Option<bool>,
+) -> Result<Vec<community::LinkRepair>, String> {
+  let state = state.lock().unwrap().clone();
+  let dry_run = dry_run.unwrap_or(false);
+  let repairs = community::reconcile_links(&state, dry_run).map_err(|e| e.to_string())?;
+
+  if !dry_run && !repairs.is_empty() && state.auto_clear_scenery_indexes {
+    let _ = sim::SimManager::new(&state).clear_scenery_indexes();
+  }
+
+  Ok(repairs)
 }
 
 #[tauri::command(async)]
@@ -179,9 +416,24 @@ pub fn rename_addon(
 pub fn verify_addon(
   state: State<'_, Mutex<AppSettings>>,
   id: &str,
+  deep: Option<bool>,
 ) -> Result<addon::VerificationResult, String> {
   let state = state.lock().unwrap().clone();
-  addon::verify_addon(&state, id).map_err(|e| e.to_string())
+  if deep.unwrap_or(false) {
+    addon::verify_addon_deep(&state, id).map_err(|e| e.to_string())
+  } else {
+    addon::verify_addon(&state, id).map_err(|e| e.to_string())
+  }
+}
+
+#[tauri::command(async)]
+pub fn check_game_versions(
+  state: State<'_, Mutex<AppSettings>>,
+) -> Result<Vec<dependency::GameVersionIssue>, String> {
+  let state = state.lock().unwrap().clone();
+  let index = dependency::PackageIndex::load(&state).map_err(|e| e.to_string())?;
+
+  Ok(index.check_game_versions())
 }
 
 #[tauri::command(async)]
@@ -202,6 +454,20 @@ pub fn reveal_addon(app_handle: AppHandle, id: &str) -> Result<(), String> {
   Ok(())
 }
 
+#[tauri::command(async)]
+pub fn repair_addon(state: State<'_, Mutex<AppSettings>>, id: &str) -> Result<Vec<String>, String> {
+  let state = state.lock().unwrap().clone();
+  archive::repair_addon(&state, id).map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn rebuild_addon_layout(state: State<'_, Mutex<AppSettings>>, id: &str) -> Result<(), String> {
+  let state = state.lock().unwrap().clone();
+  layout::rebuild_addon_layout(&state, id).map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
 #[tauri::command(async)]
 pub fn get_addon_thumbnail(
   state: State<'_, Mutex<AppSettings>>,
@@ -216,8 +482,15 @@ pub fn get_onboarding_status(app_handle: AppHandle) -> Result<bool, String> {
   settings::app_settings_exists(&app_handle).map_err(|e| e.to_string())
 }
 
+/// Lists the detected sim installs, used to prefill the community folder
+/// during onboarding.
+#[tauri::command(async)]
+pub fn detect_sim_installs() -> Vec<sim::SimInstall> {
+  sim::detect_installs()
+}
+
 #[tauri::command]
-pub fn complete_onboarding(app_handle: AppHandle, settings: AppSettings) -> Result<(), String> {
+pub fn complete_onboarding(app_handle: AppHandle, mut settings: AppSettings) -> Result<(), String> {
   let exists = settings::app_settings_exists(&app_handle).map_err(|e| e.to_string())?;
   if exists {
     return Err(String::from("Onboarding already completed"));
@@ -225,6 +498,7 @@ pub fn complete_onboarding(app_handle: AppHandle, settings: AppSettings) -> Resu
 
   let path = settings::get_app_settings_path(&app_handle).map_err(|e| e.to_string())?;
   settings.create(path).map_err(|e| e.to_string())?;
+  settings.config_dir = settings::get_aeromod_config_dir(&app_handle).ok();
 
   app_handle.manage(Mutex::new(settings));
 
@@ -243,16 +517,8 @@ pub fn update_setting(app_handle: AppHandle, key: &str, value: &str) -> Result<(
   let mut settings = state.lock().unwrap();
 
   match key {
-    "addons_dir" => {
-      let path = PathBuf::from(value);
-      if !path.exists() {
-        return Err(format!("Path '{}' does not exist", value));
-      }
-      if !path.is_dir() {
-        return Err(format!("Path '{}' is not a directory", value));
-      }
-      settings.addons_dir = path;
-    }
+    // Changing the location has to move the addons and their links.
+    "addons_dir" => return Err("Use relocate_library to change addons_dir".to_string()),
     "community_dir" => {
       let path = PathBuf::from(value);
       if !path.exists() {
@@ -282,6 +548,33 @@ pub fn update_setting(app_handle: AppHandle, key: &str, value: &str) -> Result<(
         }
       };
     }
+    "index_official_packages" => {
+      settings.index_official_packages = match value {
+        "true" => true,
+        "false" => false,
+        _ => {
+          return Err(format!(
+            "Invalid value for index_official_packages '{}'",
+            value
+          ));
+        }
+      };
+    }
+    "retain_archives" => {
+      settings.retain_archives = match value {
+        "true" => true,
+        "false" => false,
+        _ => return Err(format!("Invalid value for retain_archives '{}'", value)),
+      };
+    }
+    "activation_mode" => {
+      settings.activation_mode = match value {
+        "symlink" => ActivationMode::Symlink,
+        "hardlink" => ActivationMode::Hardlink,
+        "copy" => ActivationMode::Copy,
+        _ => return Err(format!("Invalid value for activation_mode '{}'", value)),
+      };
+    }
     _ => return Err(format!("Unknown setting key '{}'", key)),
   }
 
@@ -291,6 +584,35 @@ pub fn update_setting(app_handle: AppHandle, key: &str, value: &str) -> Result<(
   Ok(())
 }
 
+/// Moves the addons library to `new_dir`, emitting `relocate-progress` after
+/// each addon, and saves the new location in the settings.
+#[tauri::command(async)]
+pub fn relocate_library(app_handle: AppHandle, new_dir: PathBuf) -> Result<(), String> {
+  let state = app_handle.state::<Mutex<AppSettings>>();
+  // Held until the new location is saved, so that nothing installs into or
+  // saves settings for the old location in the meantime.
+  let mut settings = state.lock().unwrap();
+
+  let relocated = relocate::relocate_library(&settings, &new_dir, |progress| {
+    let _ = app_handle.emit("relocate-progress", progress);
+  })
+  .map_err(|e| e.to_string())?;
+
+  let settings_path = settings::get_app_settings_path(&app_handle).map_err(|e| e.to_string())?;
+  relocated.save(settings_path).map_err(|e| e.to_string())?;
+  *settings = relocated;
+
+  Ok(())
+}
+
+#[tauri::command(async)]
+pub fn get_pending_relocation(
+  state: State<'_, Mutex<AppSettings>>,
+) -> Result<Option<PathBuf>, String> {
+  let state = state.lock().unwrap().clone();
+  Ok(relocate::pending_relocation(&state))
+}
+
 #[tauri::command(async)]
 pub fn clear_rolling_cache(state: State<'_, Mutex<AppSettings>>) -> Result<(), String> {
   let state = state.lock().unwrap().clone();
@@ -340,7 +662,26 @@ pub fn get_preset(app_handle: AppHandle, id: &str) -> Result<preset::Preset, Str
 }
 
 #[tauri::command(async)]
-pub fn apply_preset(app_handle: AppHandle, id: &str) -> Result<(), String> {
+pub fn get_preset_dependencies(app_handle: AppHandle, id: &str) -> Result<Vec<String>, String> {
+  let state = app_handle.state::<Mutex<AppSettings>>();
+  let settings = state.lock().unwrap().clone();
+
+  let presets_dir = get_presets_dir(&app_handle)?;
+  let manager = preset::PresetManager::new(presets_dir).map_err(|e| e.to_string())?;
+
+  let preset = manager.get_preset(id).map_err(|e| e.to_string())?;
+  let metadata = metadata::MetadataStore::open(&settings).map_err(|e| e.to_string())?;
+  preset
+    .missing_dependencies(&settings, &metadata)
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command(async)]
+pub fn apply_preset(
+  app_handle: AppHandle,
+  id: &str,
+  with_dependencies: Option<bool>,
+) -> Result<Vec<String>, String> {
   let state = app_handle.state::<Mutex<AppSettings>>();
   let settings = state.lock().unwrap().clone();
 
@@ -348,13 +689,62 @@ pub fn apply_preset(app_handle: AppHandle, id: &str) -> Result<(), String> {
   let manager = preset::PresetManager::new(presets_dir).map_err(|e| e.to_string())?;
 
   let preset = manager.get_preset(id).map_err(|e| e.to_string())?;
-  preset.apply(&settings).map_err(|e| e.to_string())?;
+  let metadata = metadata::MetadataStore::open(&settings).map_err(|e| e.to_string())?;
+  let enabled_dependencies = preset
+    .apply(&settings, &metadata, with_dependencies.unwrap_or(false))
+    .map_err(|e| e.to_string())?;
 
   if settings.auto_clear_scenery_indexes {
     let _ = sim::SimManager::new(&settings).clear_scenery_indexes();
   }
 
-  Ok(())
+  Ok(enabled_dependencies)
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "source", rename_all = "lowercase")]
+pub enum FlightPlanInput {
+  Pln {
+    path: PathBuf,
+  },
+  Airports {
+    departure: Option<String>,
+    destination: Option<String>,
+    #[serde(default)]
+    alternates: Vec<String>,
+  },
+}
+
+#[tauri::command(async)]
+pub fn create_flight_plan_preset(
+  app_handle: AppHandle,
+  scenery_cache: State<'_, SceneryCacheState>,
+  name: &str,
+  flight_plan: FlightPlanInput,
+  corridor_nm: f64,
+  base_addons: Vec<String>,
+) -> Result<preset::Preset, String> {
+  let plan = match flight_plan {
+    FlightPlanInput::Pln { path } => FlightPlan::parse_pln(path).map_err(|e| e.to_string())?,
+    FlightPlanInput::Airports {
+      departure,
+      destination,
+      alternates,
+    } => FlightPlan::from_airports(departure.as_deref(), destination.as_deref(), &alternates),
+  };
+
+  let preset = {
+    let sc = scenery_cache.lock().map_err(|e| e.to_string())?;
+    plan.to_preset(&sc, name, corridor_nm, &base_addons)
+  };
+
+  let presets_dir = get_presets_dir(&app_handle)?;
+  let manager = preset::PresetManager::new(presets_dir).map_err(|e| e.to_string())?;
+  manager
+    .create_preset(preset.clone())
+    .map_err(|e| e.to_string())?;
+
+  Ok(preset)
 }
 
 #[tauri::command(async)]
@@ -394,3 +784,50 @@ pub fn rebuild_scenery_cache(
 
   Ok(sc.clone())
 }
+
+#[tauri::command(async)]
+pub fn get_scenery_summaries(
+  scenery_cache: State<'_, SceneryCacheState>,
+) -> Result<Vec<AddonScenerySummary>, String> {
+  let sc = scenery_cache.lock().map_err(|e| e.to_string())?;
+  Ok(sc.addon_summaries())
+}
+
+#[tauri::command(async)]
+pub fn get_airport_overrides(
+  scenery_cache: State<'_, SceneryCacheState>,
+  addon_id: Option<String>,
+) -> Result<Vec<AirportOverride>, String> {
+  let sc = scenery_cache.lock().map_err(|e| e.to_string())?;
+  let overrides = sc
+    .airport_overrides()
+    .into_iter()
+    .filter(|o| addon_id.as_ref().is_none_or(|id| &o.addon_id == id))
+    .collect();
+
+  Ok(overrides)
+}
+
+#[tauri::command(async)]
+pub fn export_scenery_cache(
+  app_handle: AppHandle,
+  scenery_cache: State<'_, SceneryCacheState>,
+) -> Result<Option<PathBuf>, String> {
+  let path = match app_handle
+    .dialog()
+    .file()
+    .set_file_name("scenery.geojson")
+    .add_filter("GeoJSON", &["geojson"])
+    .add_filter("KML", &["kml"])
+    .add_filter("CSV", &["csv"])
+    .blocking_save_file()
+  {
+    Some(path) => path.into_path().map_err(|e| e.to_string())?,
+    None => return Ok(None),
+  };
+
+  let sc = scenery_cache.lock().map_err(|e| e.to_string())?;
+  export::export_scenery_cache(&sc, &path).map_err(|e| e.to_string())?;
+
+  Ok(Some(path))
+}
@@ -22,7 +22,8 @@ pub fn run() {
       let exists = settings::app_settings_exists(app_handle).unwrap();
       if exists {
         let settings_path = settings::get_app_settings_path(app_handle).unwrap();
-        let settings = AppSettings::load(settings_path).expect("failed to load settings");
+        let mut settings = AppSettings::load(settings_path).expect("failed to load settings");
+        settings.config_dir = Some(config_dir);
         app.manage(Mutex::new(settings));
 
         let scenery_cache_path = app_handle
@@ -30,8 +31,10 @@ pub fn run() {
           .config_dir()
           .map(|p| p.join("AeroMod").join("scenery.json"))
           .unwrap();
-        let scenery_cache =
-          SceneryCache::load(scenery_cache_path).expect("failed to load scenery cache");
+        // An unreadable or outdated cache is discarded; the rebuild below
+        // repopulates it.
+        let scenery_cache = SceneryCache::load(&scenery_cache_path)
+          .unwrap_or_else(|_| SceneryCache::new(&scenery_cache_path));
 
         let scenery_cache_state: SceneryCacheState = Arc::new(Mutex::new(scenery_cache));
         app.manage(scenery_cache_state.clone());
@@ -62,28 +65,51 @@ pub fn run() {
     app::show_about,
     app::get_onboarding_status,
     app::complete_onboarding,
+    app::detect_sim_installs,
     app::get_addon,
     app::get_addons,
+    app::scan_addons,
+    app::list_unmanaged_packages,
+    app::import_unmanaged_package,
+    app::list_official_packages,
+    app::set_official_packages_active,
+    app::reconcile_links,
+    app::query_addons,
+    app::update_addon_metadata,
+    app::list_addon_tags,
     app::install_addon,
+    app::preview_install,
     app::enable_addon,
+    app::get_addon_dependencies,
+    app::check_dependencies,
+    app::check_game_versions,
     app::disable_addon,
     app::uninstall_addon,
     app::rename_addon,
     app::verify_addon,
+    app::repair_addon,
+    app::rebuild_addon_layout,
     app::reveal_addon,
     app::get_addon_thumbnail,
     app::clear_rolling_cache,
+    app::relocate_library,
+    app::get_pending_relocation,
     app::clear_scenery_indexes,
     app::get_settings,
     app::update_setting,
     app::create_preset,
     app::list_presets,
     app::get_preset,
+    app::get_preset_dependencies,
     app::apply_preset,
+    app::create_flight_plan_preset,
     app::update_preset,
     app::remove_preset,
     app::get_scenery_cache,
-    app::rebuild_scenery_cache
+    app::rebuild_scenery_cache,
+    app::get_scenery_summaries,
+    app::get_airport_overrides,
+    app::export_scenery_cache
   ]);
   app
     .run(tauri::generate_context!())